@@ -0,0 +1,79 @@
+use anyhow::Result;
+use clap::Args;
+use codespan_reporting::{
+    diagnostic::{Diagnostic, Label, Severity},
+    files::SimpleFiles,
+    term::{
+        self,
+        termcolor::{ColorChoice, StandardStream},
+    },
+};
+use easyflow::Dataflow;
+use std::{collections::HashMap, fs, path::PathBuf, process::ExitCode};
+
+/// Validate dataflow files and report every problem in them.
+#[derive(Debug, Args)]
+pub struct Opts {
+    /// The dataflow files to be checked.
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+}
+
+pub fn run(opts: Opts) -> Result<ExitCode> {
+    let writer = StandardStream::stderr(ColorChoice::Auto);
+    let config = term::Config::default();
+    let mut files = SimpleFiles::new();
+    let mut file_ids = HashMap::new();
+    let mut num_errors = 0;
+
+    for path in &opts.files {
+        for diag in Dataflow::check(path) {
+            if diag.severity == easyflow_config::Severity::Error {
+                num_errors += 1;
+            }
+
+            let severity = match diag.severity {
+                easyflow_config::Severity::Warning => Severity::Warning,
+                easyflow_config::Severity::Error => Severity::Error,
+            };
+            let mut report = Diagnostic::new(severity).with_message(&diag.message);
+            if let Some(lint) = diag.lint {
+                report = report.with_code(lint.name());
+            }
+
+            // point to the offending name if it is found in the file
+            match (&diag.file, &diag.span) {
+                (Some(file), Some(span)) => {
+                    let file_id = match file_ids.get(file) {
+                        Some(&file_id) => file_id,
+                        None => {
+                            let text = fs::read_to_string(file)?;
+                            let file_id = files.add(file.display().to_string(), text);
+                            file_ids.insert(file.clone(), file_id);
+                            file_id
+                        }
+                    };
+                    let label = Label::primary(file_id, span.range.clone());
+                    report = report.with_labels(vec![label]);
+                }
+                (Some(file), None) => {
+                    report = report.with_notes(vec![format!("in {}", file.display())]);
+                }
+                (None, _) => {}
+            }
+
+            if let Some(help) = diag.help() {
+                report = report.with_notes(vec![format!("help: {}", help)]);
+            }
+
+            term::emit(&mut writer.lock(), &config, &files, &report)?;
+        }
+    }
+
+    if num_errors > 0 {
+        eprintln!("found {} error(s)", num_errors);
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
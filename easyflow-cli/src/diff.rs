@@ -0,0 +1,46 @@
+use anyhow::Result;
+use clap::Args;
+use easyflow::Dataflow;
+use std::{path::PathBuf, process::ExitCode};
+
+/// The exit code on errors, which is distinct from the one on
+/// differences.
+const TROUBLE: u8 = 2;
+
+/// Compare two dataflows after modules are flattened.
+///
+/// It reports added and removed processors and exchanges, changed
+/// exchange configuration fields, and changed connections. Like
+/// diff(1), it exits with 1 if the dataflows differ, and with 2 if a
+/// dataflow cannot be loaded.
+#[derive(Debug, Args)]
+pub struct Opts {
+    /// The old dataflow file.
+    pub old: PathBuf,
+
+    /// The new dataflow file.
+    pub new: PathBuf,
+}
+
+pub fn run(opts: Opts) -> Result<ExitCode> {
+    let (old, new) = match open(&opts) {
+        Ok(dataflows) => dataflows,
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            return Ok(ExitCode::from(TROUBLE));
+        }
+    };
+    let diff = old.diff(&new);
+
+    if diff.is_empty() {
+        return Ok(ExitCode::SUCCESS);
+    }
+    print!("{}", diff);
+    Ok(ExitCode::FAILURE)
+}
+
+fn open(opts: &Opts) -> Result<(Dataflow, Dataflow)> {
+    let old = Dataflow::open(&opts.old)?;
+    let new = Dataflow::open(&opts.new)?;
+    Ok((old, new))
+}
@@ -0,0 +1,29 @@
+use anyhow::Result;
+use clap::Args;
+use easyflow::Dataflow;
+use std::{path::PathBuf, process::ExitCode};
+
+/// Write a self-contained dataflow file with modules inlined.
+///
+/// Exchange keys are resolved and `import` exchanges are replaced by
+/// the imported configurations. The output loads to the same dataflow
+/// as the input.
+#[derive(Debug, Args)]
+pub struct Opts {
+    /// The dataflow file to be flattened.
+    pub input: PathBuf,
+
+    /// The output file. The format is chosen by the file extension.
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Overlay files applied on top of the dataflow in order.
+    #[arg(long = "overlay", value_name = "FILE")]
+    pub overlays: Vec<PathBuf>,
+}
+
+pub fn run(opts: Opts) -> Result<ExitCode> {
+    let dataflow = Dataflow::open_with_overlays(&opts.input, &opts.overlays)?;
+    dataflow.save_flattened(&opts.output)?;
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,68 @@
+use anyhow::{ensure, Result};
+use clap::{Args, ValueEnum};
+use easyflow::{Dataflow, DotOptions};
+use easyflow_config::Key;
+use std::{fs, path::PathBuf, process::ExitCode};
+
+/// Draw the processors and exchanges of a dataflow.
+#[derive(Debug, Args)]
+pub struct Opts {
+    /// The dataflow file to be drawn.
+    pub input: PathBuf,
+
+    /// The output format.
+    #[arg(short, long, value_enum, default_value_t = Format::Dot)]
+    pub format: Format,
+
+    /// Write the graph to the file instead of stdout.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Draw only the processors and exchanges near the processor.
+    /// Only available for the DOT format.
+    #[arg(long, value_name = "PROCESSOR")]
+    pub neighborhood: Option<Key>,
+
+    /// The number of exchanges to walk through from the
+    /// `--neighborhood` processor.
+    #[arg(long, default_value_t = 1, requires = "neighborhood")]
+    pub radius: usize,
+
+    /// Overlay files applied on top of the dataflow in order.
+    #[arg(long = "overlay", value_name = "FILE")]
+    pub overlays: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// GraphViz DOT language
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+    /// JSON list of nodes and edges
+    Json,
+}
+
+pub fn run(opts: Opts) -> Result<ExitCode> {
+    ensure!(
+        opts.neighborhood.is_none() || matches!(opts.format, Format::Dot),
+        "--neighborhood is only available for the DOT format"
+    );
+    let dataflow = Dataflow::open_with_overlays(&opts.input, &opts.overlays)?;
+
+    let text = match opts.format {
+        Format::Dot => dataflow.to_dot_with(&DotOptions {
+            neighborhood: opts.neighborhood,
+            radius: opts.radius,
+        })?,
+        Format::Mermaid => dataflow.to_mermaid(),
+        Format::Json => serde_json::to_string_pretty(&dataflow.adjacency())?,
+    };
+
+    match opts.output {
+        Some(path) => fs::write(path, text)?,
+        None => print!("{}", text),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,286 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use easyflow::Dataflow;
+use easyflow_config::{Key, Launch, RestartPolicy};
+use std::{
+    path::{Path, PathBuf},
+    process::{ExitCode, Stdio},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, BufReader},
+    process::{Child, Command},
+    sync::watch,
+    task::JoinSet,
+};
+
+/// The delay before a program is restarted.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// The time given to a program to exit after SIGTERM before it is
+/// killed.
+const GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Start every processor in a dataflow and supervise the programs.
+///
+/// The programs are specified in the `launch` section of the
+/// dataflow file. The programs are started one after another,
+/// consumers before producers. Output lines are prefixed with the
+/// processor name. Press Ctrl-C to stop all programs. Each program
+/// receives SIGTERM and is killed if it does not exit within a grace
+/// period.
+///
+/// Each program finds the dataflow file in the `EASYFLOW_DATAFLOW`
+/// environment variable and its processor name in
+/// `EASYFLOW_PROCESSOR`. If overlays are given, the variable points to
+/// a flattened dataflow with the overlays applied.
+#[derive(Debug, Args)]
+pub struct Opts {
+    /// The dataflow file.
+    pub file: PathBuf,
+
+    /// Overlay files applied on top of the dataflow in order.
+    #[arg(long = "overlay", value_name = "FILE")]
+    pub overlays: Vec<PathBuf>,
+
+    /// Print the programs to be started without running them.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub fn run(opts: Opts) -> Result<ExitCode> {
+    let dataflow = Dataflow::open_with_overlays(&opts.file, &opts.overlays)?;
+
+    // processors open the dataflow with overlays applied
+    let flat_dir = tempfile::tempdir()?;
+    let file = if opts.overlays.is_empty() {
+        opts.file.canonicalize()?
+    } else {
+        let file = flat_dir.path().join("dataflow.json5");
+        dataflow.save_flattened(&file)?;
+        file
+    };
+
+    let order: Vec<(&Key, &Launch)> = dataflow
+        .launch_order()
+        .into_iter()
+        .filter_map(|name| match dataflow.launches().get(name) {
+            Some(launch) => Some((name, launch)),
+            None => {
+                eprintln!("warning: processor '{}' has no launch spec", name);
+                None
+            }
+        })
+        .collect();
+
+    if opts.dry_run {
+        for (name, launch) in order {
+            println!("{}: {}", name, command_line(launch));
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let ok = runtime.block_on(launch_all(&file, order))?;
+
+    Ok(if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Start the programs in order and wait for all of them to finish.
+///
+/// It returns `false` if any program fails.
+async fn launch_all(file: &Path, order: Vec<(&Key, &Launch)>) -> Result<bool> {
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let mut tasks = JoinSet::new();
+    let mut ok = true;
+
+    // Start the programs sequentially, and then hand each running
+    // program to its supervisor.
+    for (name, launch) in order {
+        let name = name.to_string();
+        let running = match start(&name, launch, file) {
+            Ok(running) => running,
+            Err(err) => {
+                eprintln!("[{}] error: {:#}", name, err);
+                ok = false;
+                continue;
+            }
+        };
+
+        let launch = launch.clone();
+        let file = file.to_owned();
+        let shutdown_rx = shutdown_rx.clone();
+        tasks.spawn(async move {
+            let result = supervise(&name, &launch, &file, running, shutdown_rx).await;
+            if let Err(err) = &result {
+                eprintln!("[{}] error: {:#}", name, err);
+            }
+            matches!(result, Ok(true))
+        });
+    }
+
+    loop {
+        tokio::select! {
+            result = tasks.join_next() => {
+                let Some(result) = result else {
+                    break;
+                };
+                ok &= result?;
+            }
+            result = tokio::signal::ctrl_c(), if !*shutdown_tx.borrow() => {
+                result?;
+                eprintln!("stopping all processors");
+                shutdown_tx.send_replace(true);
+            }
+        }
+    }
+
+    Ok(ok)
+}
+
+/// A started program along with the tasks forwarding its output.
+struct Running {
+    child: Child,
+    output: JoinSet<()>,
+}
+
+/// Wait for the running program of a processor and restart it
+/// according to the restart policy.
+///
+/// It returns `false` if the last run of the program fails.
+async fn supervise(
+    name: &str,
+    launch: &Launch,
+    file: &Path,
+    mut running: Running,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> Result<bool> {
+    loop {
+        let Running { child, output } = &mut running;
+
+        let status = tokio::select! {
+            status = child.wait() => status?,
+            _ = stopped(&mut shutdown_rx) => {
+                terminate(name, child).await?;
+                let _ = tokio::time::timeout(GRACE_PERIOD, drain(output)).await;
+                return Ok(true);
+            }
+        };
+        drain(output).await;
+        eprintln!("[{}] exited: {}", name, status);
+
+        let restart = match launch.restart {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure => !status.success(),
+            RestartPolicy::Always => true,
+        };
+        if !restart {
+            return Ok(status.success());
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(RESTART_DELAY) => {}
+            _ = stopped(&mut shutdown_rx) => return Ok(true),
+        }
+
+        running = start(name, launch, file)?;
+    }
+}
+
+/// Send SIGTERM to the program and kill it if it does not exit within
+/// the grace period.
+async fn terminate(name: &str, child: &mut Child) -> Result<()> {
+    if let Some(pid) = child.id() {
+        // SAFETY: kill() has no memory effects. The pid belongs to the
+        // child, which is not reaped before wait() returns.
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGTERM);
+        }
+
+        if let Ok(status) = tokio::time::timeout(GRACE_PERIOD, child.wait()).await {
+            eprintln!("[{}] exited: {}", name, status?);
+            return Ok(());
+        }
+        eprintln!(
+            "[{}] still running after {:?}, killing it",
+            name, GRACE_PERIOD
+        );
+    }
+
+    child.kill().await?;
+    Ok(())
+}
+
+/// Wait until all output is forwarded.
+async fn drain(output: &mut JoinSet<()>) {
+    while output.join_next().await.is_some() {}
+}
+
+/// Wait until all programs are requested to stop.
+async fn stopped(shutdown_rx: &mut watch::Receiver<bool>) {
+    let _ = shutdown_rx.wait_for(|&stop| stop).await;
+}
+
+/// Start the program of a processor.
+fn start(name: &str, launch: &Launch, file: &Path) -> Result<Running> {
+    let mut output = JoinSet::new();
+    let child = spawn(name, launch, file, &mut output)?;
+    eprintln!("[{}] started: {}", name, command_line(launch));
+    Ok(Running { child, output })
+}
+
+/// Start the program. Its output lines are forwarded by tasks in
+/// `output` with the processor name prefixed.
+fn spawn(name: &str, launch: &Launch, file: &Path, output: &mut JoinSet<()>) -> Result<Child> {
+    let mut command = Command::new(&launch.command);
+    command
+        .args(&launch.args)
+        .envs(&launch.env)
+        .env("EASYFLOW_PROCESSOR", name)
+        .env("EASYFLOW_DATAFLOW", file)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(dir) = &launch.working_dir {
+        command.current_dir(dir);
+    }
+
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("unable to start '{}'", launch.command))?;
+
+    if let Some(stdout) = child.stdout.take() {
+        output.spawn(forward(name.to_string(), BufReader::new(stdout), false));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        output.spawn(forward(name.to_string(), BufReader::new(stderr), true));
+    }
+
+    Ok(child)
+}
+
+/// Print each line from the reader with the processor name prefixed.
+async fn forward<R>(name: String, reader: R, is_stderr: bool)
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut lines = reader.lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if is_stderr {
+            eprintln!("[{}] {}", name, line);
+        } else {
+            println!("[{}] {}", name, line);
+        }
+    }
+}
+
+fn command_line(launch: &Launch) -> String {
+    let mut words = vec![launch.command.as_str()];
+    words.extend(launch.args.iter().map(String::as_str));
+    words.join(" ")
+}
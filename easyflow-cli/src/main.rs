@@ -0,0 +1,59 @@
+mod check;
+mod diff;
+mod flatten;
+mod graph;
+mod launch;
+mod migrate;
+mod schema;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use easyflow_config::MODULE_PATH_ENV;
+use std::{env, path::PathBuf, process::ExitCode};
+
+/// Tools to work with easyflow dataflow files.
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Opts {
+    /// Directories to search for package modules like
+    /// `@sensors/camera.json5`, before the ones in EASYFLOW_MODULE_PATH.
+    #[arg(long, global = true, value_name = "DIR")]
+    module_path: Vec<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    Check(check::Opts),
+    Diff(diff::Opts),
+    Flatten(flatten::Opts),
+    Graph(graph::Opts),
+    Launch(launch::Opts),
+    Migrate(migrate::Opts),
+    Schema(schema::Opts),
+}
+
+fn main() -> Result<ExitCode> {
+    let opts = Opts::parse();
+
+    // pass the search path by the environment variable, so that it is
+    // inherited by launched processors as well
+    if !opts.module_path.is_empty() {
+        let env_path = env::var_os(MODULE_PATH_ENV);
+        let env_dirs = env_path.iter().flat_map(env::split_paths);
+        let search_path = env::join_paths(opts.module_path.iter().cloned().chain(env_dirs))?;
+        env::set_var(MODULE_PATH_ENV, search_path);
+    }
+
+    match opts.command {
+        Command::Check(opts) => check::run(opts),
+        Command::Diff(opts) => diff::run(opts),
+        Command::Flatten(opts) => flatten::run(opts),
+        Command::Graph(opts) => graph::run(opts),
+        Command::Launch(opts) => launch::run(opts),
+        Command::Migrate(opts) => migrate::run(opts),
+        Command::Schema(opts) => schema::run(opts),
+    }
+}
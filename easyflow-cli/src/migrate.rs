@@ -0,0 +1,117 @@
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use easyflow_config::{migrate, migrate_file, IncludeStack, CURRENT_VERSION};
+use easyflow_link::format::Format;
+use serde_json::Value;
+use std::{
+    collections::{HashSet, VecDeque},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+/// Upgrade dataflow files and the modules they include to the current
+/// format version.
+///
+/// Files are only rewritten with `--in-place`. Comments and formatting
+/// are kept when only the version changes. Module files are followed
+/// recursively, including package modules found in the module search
+/// path.
+#[derive(Debug, Args)]
+pub struct Opts {
+    /// Report outdated files without rewriting them, and fail if any.
+    #[arg(long, conflicts_with = "in_place")]
+    pub check: bool,
+
+    /// Rewrite outdated files in place.
+    #[arg(long)]
+    pub in_place: bool,
+
+    /// The dataflow files to be upgraded.
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+}
+
+pub fn run(opts: Opts) -> Result<ExitCode> {
+    if !opts.check && !opts.in_place {
+        bail!(
+            "migrating rewrites the files. \
+             Pass --in-place to rewrite them, or --check to list outdated files"
+        );
+    }
+
+    let search_path = IncludeStack::new().search_path().to_vec();
+    let mut visited = HashSet::new();
+    let mut queue: VecDeque<PathBuf> = opts.files.iter().cloned().collect();
+    let mut num_outdated = 0;
+
+    while let Some(path) = queue.pop_front() {
+        let canonical = path
+            .canonicalize()
+            .with_context(|| format!("unable to open '{}'", path.display()))?;
+        if !visited.insert(canonical) {
+            continue;
+        }
+
+        let context = || format!("unable to migrate '{}'", path.display());
+        let mut doc: Value = Format::load(&path).with_context(context)?;
+        queue.extend(module_files(&path, &doc, &search_path));
+
+        let outdated = if opts.check {
+            migrate(&mut doc)
+        } else {
+            migrate_file(&path)
+        }
+        .with_context(context)?;
+
+        if outdated {
+            num_outdated += 1;
+            if opts.check {
+                eprintln!("{} is outdated", path.display());
+            } else {
+                eprintln!("upgraded {} to {}", path.display(), CURRENT_VERSION);
+            }
+        }
+    }
+
+    if opts.check && num_outdated > 0 {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// List the module files included by a dataflow document. Modules
+/// that cannot be found are warned and skipped.
+fn module_files(path: &Path, doc: &Value, search_path: &[PathBuf]) -> Vec<PathBuf> {
+    let Some(Value::Object(modules)) = doc.get("modules") else {
+        return vec![];
+    };
+    let dir = path.parent().unwrap_or(Path::new(""));
+
+    modules
+        .iter()
+        .filter_map(|(ident, module)| {
+            let module_path = match module {
+                Value::String(module_path) => module_path,
+                module => module.get("path")?.as_str()?,
+            };
+
+            let found = match module_path.strip_prefix('@') {
+                Some(package_path) => search_path
+                    .iter()
+                    .map(|dir| dir.join(package_path))
+                    .find(|path| path.is_file()),
+                None => Some(dir.join(module_path)).filter(|path| path.is_file()),
+            };
+            if found.is_none() {
+                eprintln!(
+                    "warning: module '{}' of {} is not found at '{}' and is not migrated",
+                    ident,
+                    path.display(),
+                    module_path
+                );
+            }
+            found
+        })
+        .collect()
+}
@@ -0,0 +1,23 @@
+use anyhow::Result;
+use clap::Args;
+use easyflow_config::graph_schema;
+use std::{fs, path::PathBuf, process::ExitCode};
+
+/// Print the JSON Schema of dataflow files.
+#[derive(Debug, Args)]
+pub struct Opts {
+    /// Write the schema to the file instead of stdout.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+pub fn run(opts: Opts) -> Result<ExitCode> {
+    let text = serde_json::to_string_pretty(&graph_schema())?;
+
+    match opts.output {
+        Some(path) => fs::write(path, text)?,
+        None => println!("{}", text),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,24 @@
+use std::process::Command;
+
+const CONFIG_DIR: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../easyflow-config/config-examples"
+);
+
+fn diff(old: &str, new: &str) -> Option<i32> {
+    Command::new(env!("CARGO_BIN_EXE_easyflow"))
+        .current_dir(CONFIG_DIR)
+        .args(["diff", old, new])
+        .output()
+        .unwrap()
+        .status
+        .code()
+}
+
+#[test]
+fn diff_exit_code_test() {
+    assert_eq!(diff("diff/old.json5", "diff/old.json5"), Some(0));
+    assert_eq!(diff("diff/old.json5", "diff/new.json5"), Some(1));
+    assert_eq!(diff("diff/old.json5", "diff/missing.json5"), Some(2));
+    assert_eq!(diff("cyclic/first.json5", "diff/new.json5"), Some(2));
+}
@@ -0,0 +1,107 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader},
+    process::{Command, Stdio},
+};
+
+const CONFIG_FILE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../easyflow-config/config-examples/launch.json5"
+);
+
+#[test]
+fn launch_dry_run_test() {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyflow"))
+        .args(["launch", "--dry-run", CONFIG_FILE])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let names: Vec<_> = stdout
+        .lines()
+        .map(|line| line.split(':').next().unwrap())
+        .collect();
+    assert_eq!(names, ["recorder", "merger", "camera"]);
+}
+
+#[test]
+fn launch_test() {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyflow"))
+        .args(["launch", CONFIG_FILE])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines: Vec<_> = stdout.lines().collect();
+    lines.sort();
+    assert_eq!(
+        lines,
+        [
+            "[camera] capturing",
+            "[merger] merging for merger",
+            "[recorder] recording",
+        ]
+    );
+}
+
+#[test]
+fn launch_order_test() {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyflow"))
+        .args(["launch", CONFIG_FILE])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let started: Vec<_> = stderr
+        .lines()
+        .filter(|line| line.contains("] started: "))
+        .map(|line| line[1..].split(']').next().unwrap())
+        .collect();
+    assert_eq!(started, ["recorder", "merger", "camera"]);
+}
+
+#[test]
+fn launch_terminate_test() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("dataflow.json5");
+    fs::write(
+        &file,
+        r#"{
+            "version": "0.2.0",
+            "processors": ["worker"],
+            "exchanges": {},
+            "connections": {},
+            "launch": {
+                "worker": {
+                    "command": "sh",
+                    "args": [
+                        "-c",
+                        "trap 'echo terminated; exit 0' TERM; echo ready; while true; do sleep 0.1; done",
+                    ],
+                },
+            },
+        }"#,
+    )
+    .unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_easyflow"))
+        .arg("launch")
+        .arg(&file)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    assert_eq!(lines.next().unwrap().unwrap(), "[worker] ready");
+
+    // Press Ctrl-C.
+    unsafe {
+        libc::kill(child.id() as libc::pid_t, libc::SIGINT);
+    }
+
+    assert_eq!(lines.next().unwrap().unwrap(), "[worker] terminated");
+    assert!(child.wait().unwrap().success());
+}
@@ -0,0 +1,69 @@
+use std::{fs, path::Path, process::Command};
+
+const LEGACY: &str = r#"{
+    "version": "0.1.0",
+    "processors": [],
+    "exchanges": {},
+    "connections": {},
+    "modules": {
+        // the camera module is migrated as well
+        "cam": "camera.json5",
+    },
+}"#;
+
+const CAMERA: &str = r#"{
+    "version": "0.1.0",
+    "processors": ["camera"],
+    "exchanges": {},
+    "connections": {},
+}"#;
+
+fn migrate(args: &[&str], file: &Path) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyflow"))
+        .arg("migrate")
+        .args(args)
+        .arg(file)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    (output.status.success(), stderr)
+}
+
+#[test]
+fn migrate_modules_test() {
+    let dir = tempfile::tempdir().unwrap();
+    let main = dir.path().join("main.json5");
+    let camera = dir.path().join("camera.json5");
+    fs::write(&main, LEGACY).unwrap();
+    fs::write(&camera, CAMERA).unwrap();
+
+    // files are not rewritten without --in-place
+    let (success, stderr) = migrate(&[], &main);
+    assert!(!success);
+    assert!(stderr.contains("--in-place"));
+    assert_eq!(fs::read_to_string(&main).unwrap(), LEGACY);
+
+    // modules are checked along with the including file
+    let (success, stderr) = migrate(&["--check"], &main);
+    assert!(!success);
+    let outdated: Vec<_> = stderr.lines().collect();
+    assert_eq!(
+        outdated,
+        [
+            format!("{} is outdated", main.display()),
+            format!("{} is outdated", camera.display()),
+        ]
+    );
+
+    let (success, _) = migrate(&["--in-place"], &main);
+    assert!(success);
+    // only the versions are replaced, keeping comments
+    for (file, orig) in [(&main, LEGACY), (&camera, CAMERA)] {
+        let expect = orig.replace("0.1.0", "0.2.0");
+        assert_eq!(fs::read_to_string(file).unwrap(), expect);
+    }
+
+    let (success, stderr) = migrate(&["--check"], &main);
+    assert!(success);
+    assert!(stderr.is_empty());
+}
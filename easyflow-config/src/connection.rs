@@ -1,52 +1,70 @@
-use crate::ident::Ident;
+use crate::{ident::Ident, key::Key};
 use indexmap::IndexSet;
+use itertools::chain;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// It describes incoming sinks and outgoing sources to processors of an exchange.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct Connection {
     /// The set of processors which inputs the connection is going into.
     #[serde(rename = "<")]
-    pub sink: Option<IndexSet<Ident>>,
+    pub sink: Option<IndexSet<Key>>,
     /// The set of processors which outputs the connection is coming from.
     #[serde(rename = ">")]
-    pub source: Option<IndexSet<Ident>>,
+    pub source: Option<IndexSet<Key>>,
 }
 
 impl Connection {
     /// Iterate over sink names.
-    pub fn sink_iter(&self) -> impl Iterator<Item = &Ident> {
+    pub fn sink_iter(&self) -> impl Iterator<Item = &Key> {
         self.sink.as_ref().into_iter().flatten()
     }
 
     /// Iterate over source names.
-    pub fn source_iter(&self) -> impl Iterator<Item = &Ident> {
+    pub fn source_iter(&self) -> impl Iterator<Item = &Key> {
         self.source.as_ref().into_iter().flatten()
     }
 
-    // pub fn merge(self, other: Self) -> Self {
-    //     Self {
-    //         sink: merge_set(self.sink, other.sink),
-    //         source: merge_set(self.source, other.source),
-    //     }
-    // }
+    /// Prepend an identifier sequence to sink and source names.
+    pub fn prepend<I>(&mut self, idents: I)
+    where
+        I: IntoIterator<Item = Ident> + Clone,
+    {
+        let prepend_set = |set: IndexSet<Key>| -> IndexSet<Key> {
+            set.into_iter()
+                .map(|mut key| {
+                    key.prepend(idents.clone());
+                    key
+                })
+                .collect()
+        };
+        self.sink = self.sink.take().map(prepend_set);
+        self.source = self.source.take().map(prepend_set);
+    }
+
+    /// Merge with the other connection to the same exchange.
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            sink: merge_set(self.sink, other.sink),
+            source: merge_set(self.source, other.source),
+        }
+    }
 
-    // pub fn merge_with(&mut self, other: Self) {
-    //     *self = Self {
-    //         sink: merge_set(self.sink.take(), other.sink),
-    //         source: merge_set(self.source.take(), other.source),
-    //     };
-    // }
+    /// Merge the other connection to the same exchange into this one.
+    pub fn merge_with(&mut self, other: Self) {
+        *self = Self {
+            sink: merge_set(self.sink.take(), other.sink),
+            source: merge_set(self.source.take(), other.source),
+        };
+    }
 }
 
-// fn merge_set(
-//     lhs: Option<IndexSet<Ident>>,
-//     rhs: Option<IndexSet<Ident>>,
-// ) -> Option<IndexSet<Ident>> {
-//     match (lhs, rhs) {
-//         (Some(lhs), Some(rhs)) => Some(chain!(lhs, rhs).collect()),
-//         (Some(lhs), None) => Some(lhs),
-//         (None, Some(rhs)) => Some(rhs),
-//         (None, None) => None,
-//     }
-// }
+fn merge_set(lhs: Option<IndexSet<Key>>, rhs: Option<IndexSet<Key>>) -> Option<IndexSet<Key>> {
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => Some(chain!(lhs, rhs).collect()),
+        (Some(lhs), None) => Some(lhs),
+        (None, Some(rhs)) => Some(rhs),
+        (None, None) => None,
+    }
+}
@@ -0,0 +1,324 @@
+use crate::{Lint, LintLevel};
+use anyhow::{bail, Result};
+use std::{
+    collections::HashMap,
+    fmt,
+    fmt::Display,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// The severity of a [Diagnostic].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Warning => "warning".fmt(f),
+            Self::Error => "error".fmt(f),
+        }
+    }
+}
+
+/// A location in a configuration file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// The byte range in the file.
+    pub range: Range<usize>,
+    /// The line number starting from 1.
+    pub line: usize,
+    /// The column number in characters starting from 1.
+    pub column: usize,
+}
+
+impl Span {
+    /// Find the name written in the `section` of the configuration text.
+    ///
+    /// A quoted string equal to the name is preferred, so that `CAM`
+    /// is not found within `"CAM.RAW"`. Otherwise, the name is matched
+    /// as a whole word to find unquoted keys in JSON5, TOML and YAML
+    /// files. It falls back to the whole text if the name is not found
+    /// after the section.
+    pub fn locate(text: &str, section: &str, name: &str) -> Option<Self> {
+        let find = |start| find_quoted(text, start, name).or_else(|| find_word(text, start, name));
+        let start = find_word(text, 0, section).map(|range| range.end);
+        let range = start.and_then(find).or_else(|| find(0))?;
+        Some(Self::from_range(text, range))
+    }
+
+    /// Create from a byte range in the text.
+    pub fn from_range(text: &str, range: Range<usize>) -> Self {
+        let prefix = &text[..range.start];
+        let line_start = prefix.rfind('\n').map(|pos| pos + 1).unwrap_or(0);
+        let line = prefix.matches('\n').count() + 1;
+        let column = prefix[line_start..].chars().count() + 1;
+        Self {
+            range,
+            line,
+            column,
+        }
+    }
+}
+
+/// A problem found in a configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// The file where the problem is found.
+    pub file: Option<PathBuf>,
+    /// The location of the offending name in the file.
+    pub span: Option<Span>,
+    /// A declared name similar to the offending one.
+    pub suggestion: Option<String>,
+    /// The lint that reports the problem, if any.
+    pub lint: Option<Lint>,
+}
+
+impl Diagnostic {
+    /// Create an error without a location.
+    pub fn error<M>(message: M) -> Self
+    where
+        M: Into<String>,
+    {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            file: None,
+            span: None,
+            suggestion: None,
+            lint: None,
+        }
+    }
+
+    /// The help message on the suggestion, if any.
+    pub fn help(&self) -> Option<String> {
+        let suggestion = self.suggestion.as_ref()?;
+        Some(format!("did you mean `{}`?", suggestion))
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+            if let Some(span) = &self.span {
+                write!(f, "{}:{}:", span.line, span.column)?;
+            }
+            write!(f, " ")?;
+        }
+        write!(f, "{}", self.severity)?;
+        if let Some(lint) = &self.lint {
+            write!(f, "[{}]", lint)?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(help) = self.help() {
+            write!(f, "\n  help: {}", help)?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the most similar candidate to the misspelled name.
+pub fn suggest<'a, I>(name: &str, candidates: I) -> Option<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|cand| (strsim::levenshtein(name, cand), cand))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, cand)| cand.to_string())
+}
+
+/// Decide what to do with the problems found during flattening.
+pub(crate) enum Report {
+    /// Return an error on the first problem.
+    FailFast,
+    /// Record every problem and keep going.
+    Collect {
+        root: Option<PathBuf>,
+        diagnostics: Vec<Diagnostic>,
+        sources: HashMap<PathBuf, Option<String>>,
+    },
+}
+
+impl Report {
+    /// Create a report that records problems in the root file `root`
+    /// and its modules. The root file may be unknown.
+    pub fn collect(root: Option<&Path>) -> Self {
+        Self::Collect {
+            root: root.map(Path::to_owned),
+            diagnostics: vec![],
+            sources: HashMap::new(),
+        }
+    }
+
+    /// Report a problem on the name written in the `section` of the
+    /// file. The `file` is `None` for the root file.
+    pub fn error(
+        &mut self,
+        file: Option<&Path>,
+        section: &str,
+        name: &str,
+        message: String,
+        suggestion: Option<String>,
+    ) -> Result<()> {
+        let Self::Collect { .. } = self else {
+            bail!(message);
+        };
+
+        let diagnostic = Diagnostic {
+            suggestion,
+            ..Diagnostic::error(message)
+        };
+        self.push_located(file, section, name, diagnostic);
+        Ok(())
+    }
+
+    /// Report a lint on the name written in the `section` of the
+    /// file. A denied lint is an error, while a warned lint is
+    /// dropped unless the problems are collected.
+    pub fn lint(
+        &mut self,
+        file: Option<&Path>,
+        section: &str,
+        name: &str,
+        lint: Lint,
+        level: LintLevel,
+        message: String,
+    ) -> Result<()> {
+        let severity = match (&*self, level) {
+            (_, LintLevel::Allow) | (Self::FailFast, LintLevel::Warn) => return Ok(()),
+            (Self::FailFast, LintLevel::Deny) => {
+                bail!("{} (denied by lint '{}')", message, lint)
+            }
+            (Self::Collect { .. }, LintLevel::Warn) => Severity::Warning,
+            (Self::Collect { .. }, LintLevel::Deny) => Severity::Error,
+        };
+
+        let diagnostic = Diagnostic {
+            severity,
+            lint: Some(lint),
+            ..Diagnostic::error(message)
+        };
+        self.push_located(file, section, name, diagnostic);
+        Ok(())
+    }
+
+    /// Report an error on loading the module `name`.
+    pub fn module_error(
+        &mut self,
+        file: Option<&Path>,
+        name: &str,
+        error: anyhow::Error,
+    ) -> Result<()> {
+        match self {
+            Self::FailFast => Err(error),
+            Self::Collect { .. } => self.error(file, "modules", name, format!("{:#}", error), None),
+        }
+    }
+
+    /// Record a diagnostic as is.
+    pub fn push(&mut self, diagnostic: Diagnostic) -> Result<()> {
+        match self {
+            Self::FailFast => bail!(diagnostic.message),
+            Self::Collect { diagnostics, .. } => {
+                diagnostics.push(diagnostic);
+                Ok(())
+            }
+        }
+    }
+
+    /// Record a diagnostic with the location of the name written in
+    /// the `section` of the file. The `file` is `None` for the root
+    /// file.
+    fn push_located(
+        &mut self,
+        file: Option<&Path>,
+        section: &str,
+        name: &str,
+        diagnostic: Diagnostic,
+    ) {
+        let Self::Collect {
+            root,
+            diagnostics,
+            sources,
+        } = self
+        else {
+            return;
+        };
+
+        let file = file.or(root.as_deref());
+        let span = file.and_then(|file| {
+            let text = sources
+                .entry(file.to_owned())
+                .or_insert_with(|| fs::read_to_string(file).ok());
+            text.as_deref()
+                .and_then(|text| Span::locate(text, section, name))
+        });
+
+        diagnostics.push(Diagnostic {
+            file: file.map(Path::to_owned),
+            span,
+            ..diagnostic
+        });
+    }
+
+    /// Return true if an error is recorded.
+    pub fn has_errors(&self) -> bool {
+        match self {
+            Self::FailFast => false,
+            Self::Collect { diagnostics, .. } => diagnostics
+                .iter()
+                .any(|diag| diag.severity == Severity::Error),
+        }
+    }
+
+    /// Take the recorded diagnostics.
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        match self {
+            Self::FailFast => vec![],
+            Self::Collect { diagnostics, .. } => diagnostics,
+        }
+    }
+}
+
+/// Find the quoted string in the text starting from the byte offset
+/// `start`. The returned range excludes the quotes.
+fn find_quoted(text: &str, start: usize, name: &str) -> Option<Range<usize>> {
+    ['"', '\'']
+        .into_iter()
+        .filter_map(|quote| {
+            let quoted = format!("{quote}{name}{quote}");
+            let begin = start + text[start..].find(&quoted)? + 1;
+            Some(begin..(begin + name.len()))
+        })
+        .min_by_key(|range| range.start)
+}
+
+/// Find the word in the text starting from the byte offset `start`.
+fn find_word(text: &str, start: usize, word: &str) -> Option<Range<usize>> {
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || "-_/".contains(c);
+    let mut offset = start;
+
+    while let Some(pos) = text[offset..].find(word) {
+        let begin = offset + pos;
+        let end = begin + word.len();
+        let before = text[..begin].chars().next_back();
+        let after = text[end..].chars().next();
+
+        if !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char) {
+            return Some(begin..end);
+        }
+        offset = begin + word.len().max(1);
+    }
+    None
+}
@@ -15,6 +15,18 @@ impl Dir {
     pub fn new(idents: Vec<Ident>) -> Self {
         Self(idents)
     }
+
+    /// Return true if the sequence is empty, referring to the root graph.
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Return the directory without the last identifier, or `None`
+    /// if it is the root.
+    pub fn parent(&self) -> Option<Dir> {
+        let (_, parent) = self.0.split_last()?;
+        Some(Dir(parent.to_vec()))
+    }
 }
 
 impl Serialize for Dir {
@@ -1,137 +1,615 @@
-use crate::{Connection, Dir, Ident, Key};
+use crate::{
+    diagnostic::{suggest, Diagnostic, Report},
+    loader::load_graph,
+    subst::{escape_placeholders, Variables},
+    Connection, Dir, Ident, IncludeStack, Key, Launch, Lint, LintLevel, Module, Overlay,
+    CURRENT_VERSION,
+};
 use anyhow::{ensure, Result};
+use easyflow_link::format::Format;
 use easyflow_link::Config as Exchange;
 use indexmap::{IndexMap, IndexSet};
 use itertools::chain;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Metadata, Schema, SchemaObject},
+    JsonSchema,
+};
 use serde::{Deserialize, Serialize};
-use serde_loader::Json5Path;
 use serde_semver::SemverReq;
-use std::path::PathBuf;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, SemverReq)]
-#[version("0.1.0")]
+#[version("0.2.0")]
 pub struct Version;
 
+impl JsonSchema for Version {
+    fn schema_name() -> String {
+        "Version".into()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            metadata: Some(Box::new(Metadata {
+                description: Some("The format version of the file.".into()),
+                examples: vec![CURRENT_VERSION.into()],
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
 /// The serialized/deserialized graph configuration.
 ///
 /// The graph configurations is valid if
 /// - Processor and exchange names are valid identifiers.
-/// - Connection identifiers refer to declared exchanges.
+/// - Connection keys refer to exchanges declared in the configuration or its modules.
 /// - Connection sources and sinks are declared processors within the configuration.
 ///
 /// The configuration consists of these components.
 /// - `processors`: The processor name list.
 /// - `exchanges`: The list of data exchange names and configurations.
 /// - `connections`: Defines in/output connections to prorcessors for each exchange.
-/// - `modules`: Named external configuration files to be included.
+/// - `modules`: Named external configuration files to be included, optionally with arguments.
+/// - `launch`: Optional programs to be started for processors. See [Launch].
+/// - `lints`: Optional levels of lints on the flattened graph. See [Lint].
 ///
 /// # Processor Namespace
-/// Processor names included from modules are placed in a flat namespace. For example,
-/// a module `outer` declares a processor `myproc`. The processor is reference by `myproc`
-/// (but not `outer/myproc`).
+/// By default, processor names included from modules are placed in a flat namespace.
+/// For example, a module `outer` declares a processor `myproc`. The processor is
+/// reference by `myproc` (but not `outer/myproc`). A flat name can be declared by
+/// a module and its including graph to connect them, but two sibling modules may not
+/// declare the same flat name unless the including graph declares it as well.
+///
+/// A configuration file opts in to scoped processor names by setting
+/// `"processor_namespace": "scoped"`. Its processors are then prefixed by the module
+/// names like exchanges, so the processor above is referenced by `outer/myproc`.
+/// It allows a module to be included more than once.
+///
+/// Processor and exchange names may also be written as keys like `outer/myproc`.
+/// They are declared as is and prefixed only by the enclosing modules. This form
+/// is used by self-contained graphs produced by [GraphUnchecked::into_config].
 ///
 /// # Exchange Namespace
 /// The exchange namespace is tree-structured defined by modules.
 /// For example, a module named `outer` has an exchange `myexchange`. The exchange
 /// is referenced by the key `outer/myexchange`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Connections are keyed by exchange keys. A graph can connect its processors to
+/// exchanges declared in its modules, for example `"outer/myexchange": {">": ["myproc"]}`.
+/// The connection is merged with the module's own connection to that exchange.
+///
+/// Each key is declared only once. For example, a graph declaring the exchange
+/// `outer/myexchange` by the key is rejected if the module `outer` declares
+/// `myexchange`. The same holds for scoped processor names.
+///
+/// # Module Includes
+/// Module files are loaded when the configuration is flattened. A file
+/// may be included more than once, for example by two modules that share
+/// a common dependency. Each inclusion is flattened separately under its
+/// own module prefix. A module that includes itself, directly or through
+/// other modules, is rejected with an [IncludeCycle](crate::IncludeCycle)
+/// error that lists the include chain.
+///
+/// # Module Arguments
+/// A module can be included with arguments, which are substituted into
+/// `${name}` placeholders in the exchange configurations of the module.
+/// It allows one module file to be instantiated several times with
+/// different exchange parameters.
+///
+/// ```json5
+/// "modules": {
+///     "cam0": { "path": "camera.json5", "args": { "index": 0 } },
+///     "cam1": { "path": "camera.json5", "args": { "index": 1 } },
+/// }
+/// ```
+///
+/// The module is rejected if an argument is not used by any placeholder.
+///
+/// # Package Modules
+/// A module path starting with `@`, such as `@sensors/camera.json5`, is
+/// searched in the module search path instead of the directory of the
+/// including file. The search path is given by
+/// [IncludeStack::with_search_path] followed by the directories in the
+/// [MODULE_PATH_ENV](crate::MODULE_PATH_ENV) environment variable. The
+/// file bindings of the flattened graph record where modules are found.
+///
+/// # Variable Substitution
+/// Placeholders `${name}` and `${name:-default}` can be written in exchange
+/// configurations, launch specs and module arguments of any configuration file. A name
+/// is looked up in the following order.
+///
+/// 1. Module arguments given by the including graph.
+/// 2. Built-in variables.
+///    - `config_dir`: The directory containing the configuration file.
+///    - `module`: The module namespace, e.g. `outer/inner`. It is empty for the root graph.
+/// 3. Environment variables.
+///
+/// The default value after `:-` is used if the name is not defined.
+/// Otherwise, it is an error. A string that is exactly one placeholder
+/// takes the JSON type of the value, while a placeholder within a string
+/// is formatted into the text. Write `$${` for a literal `${`.
+///
+/// ```json5
+/// "exchanges": {
+///     "VIDEO": {
+///         "type": "zenoh",
+///         "key": "${module}/${VIDEO_TOPIC:-video}",
+///     },
+///     "OUTPUT": {
+///         "type": "file",
+///         "dir": "${config_dir}/output",
+///     },
+/// }
+/// ```
+///
+/// Substitution is applied by [GraphConfig::open] and when modules are
+/// loaded during flattening.
+///
+/// # Format Versions
+/// The `version` field declares the format version of the file. The
+/// current version is [CURRENT_VERSION](crate::CURRENT_VERSION).
+/// [GraphConfig::open] accepts older versions and upgrades them in
+/// memory by [migrate](crate::migrate), while deserializing a
+/// [GraphConfig] directly only accepts the current version.
+///
+/// - `0.1.0`: The initial version. `${` is substituted as in `0.2.0`,
+///   since releases before the version bump already did so.
+/// - `0.2.0`: Variable substitution is introduced.
+///
+/// # Lints
+/// A valid graph may still contain mistakes, such as an exchange that
+/// no processor sends to. [Lint]s check the flattened graph for these
+/// cases. Only the `lints` section of the root file takes effect. A
+/// denied lint fails [flatten](GraphConfig::flatten), while warnings
+/// are reported by [check](GraphConfig::check) and
+/// [GraphUnchecked::lint].
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(
+    title = "Dataflow",
+    description = "The easyflow dataflow configuration."
+)]
 pub struct GraphConfig {
     /// Format version
     pub version: Version,
     /// Processor name declarations.
-    pub processors: IndexSet<Ident>,
+    pub processors: IndexSet<Key>,
     /// Exchange name declarations.
-    pub exchanges: IndexMap<Ident, Exchange>,
+    pub exchanges: IndexMap<Key, Exchange>,
     /// Connection configurations for local and remote exchanges.
-    pub connections: IndexMap<Ident, Connection>,
+    pub connections: IndexMap<Key, Connection>,
     /// Outer graph configurations to be included.
-    pub modules: Option<IndexMap<Ident, Json5Path<GraphConfig>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modules: Option<IndexMap<Ident, Module>>,
+    /// The namespace of processors declared in this configuration.
+    #[serde(default, skip_serializing_if = "ProcessorNamespace::is_flat")]
+    pub processor_namespace: ProcessorNamespace,
+    /// Programs to be started for processors.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub launch: IndexMap<Key, Launch>,
+    /// The levels of lints. Unlisted lints are warned.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub lints: IndexMap<Lint, LintLevel>,
+}
+
+/// The namespace where the processors of a graph configuration are placed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessorNamespace {
+    /// Processors are referenced by their names regardless of the enclosing modules.
+    #[default]
+    Flat,
+    /// Processors are prefixed by the enclosing module names.
+    Scoped,
+}
+
+impl ProcessorNamespace {
+    /// Return true if processors are placed in the flat namespace.
+    pub fn is_flat(&self) -> bool {
+        *self == Self::Flat
+    }
 }
 
 /// The intermediate working graph data structure.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GraphUnchecked {
-    pub bindings: IndexMap<PathBuf, Dir>,
-    pub processors: IndexSet<Ident>,
+    pub bindings: IndexMap<Dir, PathBuf>,
+    pub processors: IndexSet<Key>,
     pub exchanges: IndexMap<Key, Exchange>,
     pub connections: IndexMap<Key, Connection>,
+    pub launch: IndexMap<Key, Launch>,
+    /// The module declaring each processor. Processors declared by
+    /// the root graph are in the empty directory.
+    pub declarations: IndexMap<Key, Dir>,
 }
 
 impl GraphUnchecked {
     /// Merge with the other graph without checking validity.
+    ///
+    /// Exchanges in the other graph replace those with the same keys.
+    /// [flatten](GraphConfig::flatten) rejects such duplicates before
+    /// merging modules.
     pub fn merge_unchecked(mut self, other: Self) -> Self {
         // join processor identifiers
-        // it's fine that processors appear more than once
+        // flat processors shared with modules appear more than once
         self.processors.extend(other.processors);
 
-        // combine exchanges and connections
-        // assume that exchange identifiers are prepend with a module name
-        // so let's join without checks
+        // combine exchanges
+        // duplicate keys are rejected when modules are flattened
         self.exchanges.extend(other.exchanges);
-        self.connections.extend(other.connections);
+
+        // combine connections
+        // a graph may connect to exchanges in its modules, so merge connections on the same key
+        for (key, conn) in other.connections {
+            match self.connections.get_mut(&key) {
+                Some(orig) => orig.merge_with(conn),
+                None => {
+                    self.connections.insert(key, conn);
+                }
+            }
+        }
 
         // combine file bindings
-        // file paths are unique of there is no cyclic module references
+        // module directories are unique because each one is prefixed by its parents
         self.bindings.extend(other.bindings);
 
+        // combine launch specs
+        // the including graph overrides the specs of shared processors in its modules
+        for (key, launch) in other.launch {
+            self.launch.entry(key).or_insert(launch);
+        }
+
+        // a processor shared with modules is declared by the including graph
+        for (key, dir) in other.declarations {
+            self.declarations.entry(key).or_insert(dir);
+        }
+
         self
     }
+
+    /// Convert to a self-contained graph configuration without modules.
+    ///
+    /// Processors and exchanges are declared by their full keys, and
+    /// file bindings are dropped. The configuration is flattened to a
+    /// graph identical to this one.
+    pub fn into_config(self) -> GraphConfig {
+        let Self {
+            processors,
+            exchanges,
+            connections,
+            launch,
+            ..
+        } = self;
+
+        GraphConfig {
+            version: Version,
+            processors,
+            exchanges,
+            connections,
+            launch,
+            modules: None,
+            processor_namespace: ProcessorNamespace::Flat,
+            lints: IndexMap::new(),
+        }
+    }
+
+    /// Check that connections and launch specs refer to declared
+    /// exchanges and processors.
+    ///
+    /// A graph flattened from a configuration is always valid. It is
+    /// used to check the graph modified afterwards.
+    pub fn validate(&self) -> Result<()> {
+        for (key, conn) in &self.connections {
+            ensure!(
+                self.exchanges.contains_key(key),
+                "'{}' is not a declared exchange",
+                key
+            );
+
+            for proc in chain!(conn.sink_iter(), conn.source_iter()) {
+                ensure!(
+                    self.processors.contains(proc),
+                    "'{}' is not a declared processor",
+                    proc
+                );
+            }
+        }
+
+        for key in self.launch.keys() {
+            ensure!(
+                self.processors.contains(key),
+                "'{}' is launched but not a declared processor",
+                key
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Run every lint on the graph with the given levels.
+    ///
+    /// Warned lints are reported as warnings and denied lints as
+    /// errors. Problems in the root graph have no file locations.
+    pub fn lint(&self, levels: &IndexMap<Lint, LintLevel>) -> Vec<Diagnostic> {
+        let mut report = Report::collect(None);
+        self.lint_with(levels, &mut report).unwrap();
+        report.into_diagnostics()
+    }
 }
 
 impl GraphConfig {
+    /// Open a graph configuration file.
+    ///
+    /// The file is parsed as TOML if the extension is `.toml`, as YAML if
+    /// it is `.yaml` or `.yml`, and as JSON5 otherwise. Modules can be
+    /// written in any of these formats.
+    ///
+    /// Variables are substituted into exchange configurations and module
+    /// arguments. Modules are not loaded until the graph is flattened.
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        load_graph(path.as_ref(), Variables::default())
+    }
+
+    /// Save the configuration to a file. The format is chosen by the
+    /// file extension like [open](GraphConfig::open).
+    ///
+    /// `${` in exchange configurations, launch specs and module arguments is escaped
+    /// to `$${`, so that the strings are not substituted when the file
+    /// is opened again. Module paths are written as they are given,
+    /// which are relative to the original file.
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut doc = serde_json::to_value(self)?;
+        escape_placeholders(&mut doc);
+        let text = Format::from_path(path).to_string(&doc)?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Open a graph configuration file and report every problem in it
+    /// and its modules.
+    ///
+    /// Unlike [flatten](GraphConfig::flatten), it does not stop at the
+    /// first problem. Each diagnostic records the file where the problem
+    /// is found, the location of the offending name and a suggestion
+    /// for misspelled names. Warnings from [Lint]s are included if the
+    /// graph is valid. It returns an empty list if the graph is valid
+    /// and no lint fires.
+    pub fn check<P>(path: P) -> Vec<Diagnostic>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut report = Report::collect(Some(path));
+
+        let result = Self::open(path).and_then(|config| {
+            let lints = config.lints.clone();
+            let graph = config.flatten_with(&mut IncludeStack::new(), &mut report)?;
+
+            // lints on a broken graph are noise
+            if !report.has_errors() {
+                graph.lint_with(&lints, &mut report)?;
+            }
+            Ok(())
+        });
+        if let Err(err) = result {
+            let diagnostic = Diagnostic {
+                file: Some(path.to_owned()),
+                ..Diagnostic::error(format!("{:#}", err))
+            };
+            report.push(diagnostic).unwrap();
+        }
+
+        report.into_diagnostics()
+    }
+
     /// The entry point to flatten the modules of a graph configuration.
+    ///
+    /// It fails if a [Lint] denied by this configuration fires.
     pub fn flatten(self) -> Result<GraphUnchecked> {
-        self.flatten_recursive(&mut vec![])
+        self.flatten_with_overlays::<_, &Path>([])
+    }
+
+    /// Flatten the modules and apply the [Overlay] files in order.
+    ///
+    /// The graph is validated again after the overlays are applied,
+    /// and then linted by the levels of this configuration.
+    pub fn flatten_with_overlays<I, P>(self, overlays: I) -> Result<GraphUnchecked>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.flatten_in(&mut IncludeStack::new(), overlays)
+    }
+
+    /// Flatten the modules within the empty `stack` and apply the
+    /// [Overlay] files in order.
+    ///
+    /// Package modules are searched in the
+    /// [search path](IncludeStack::search_path) of the stack. See
+    /// [flatten_with_overlays](GraphConfig::flatten_with_overlays) for
+    /// details.
+    pub fn flatten_in<I, P>(self, stack: &mut IncludeStack, overlays: I) -> Result<GraphUnchecked>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let lints = self.lints.clone();
+        let mut graph = self.flatten_recursive(stack)?;
+
+        for path in overlays {
+            let overlay = Overlay::open(path, &graph.exchanges)?;
+            overlay.apply(&mut graph)?;
+            graph.validate()?;
+        }
+
+        graph.lint_with(&lints, &mut Report::FailFast)?;
+        Ok(graph)
     }
 
     /// The recursive function that flatten the modules of a graph configuration.
-    pub fn flatten_recursive(self, prefix: &mut Vec<Ident>) -> Result<GraphUnchecked> {
+    ///
+    /// The `stack` records the modules enclosing this graph. Module files
+    /// are loaded here and pushed onto the stack before being flattened.
+    pub fn flatten_recursive(self, stack: &mut IncludeStack) -> Result<GraphUnchecked> {
+        self.flatten_with(stack, &mut Report::FailFast)
+    }
+
+    /// Flatten the graph and pass found problems to the `report`.
+    ///
+    /// The problems are recorded and skipped if the report collects
+    /// them. Otherwise, it returns an error on the first problem.
+    fn flatten_with(self, stack: &mut IncludeStack, report: &mut Report) -> Result<GraphUnchecked> {
+        // the file that declares this graph. It is None for the root graph.
+        let file: Option<PathBuf> = stack.includes().last().map(|inc| inc.path.clone());
+        let file = file.as_deref();
+
         // check if connection sinks and sources refer to declared processors
-        self.connections
+        for key in self
+            .connections
             .values()
             .flat_map(|conn| chain!(conn.sink_iter(), conn.source_iter()))
-            .try_for_each(|ident| {
-                ensure!(
-                    self.processors.contains(ident),
-                    "'{}' is not a declared processor",
-                    ident
-                );
-                Ok(())
-            })?;
+        {
+            if !self.processors.contains(key) {
+                let name = key.to_string();
+                let candidates: Vec<_> = self.processors.iter().map(Key::to_string).collect();
+                let suggestion = suggest(&name, candidates.iter().map(String::as_str));
+                report.error(
+                    file,
+                    "connections",
+                    &name,
+                    format!("'{}' is not a declared processor", key),
+                    suggestion,
+                )?;
+            }
+        }
 
-        // check that connections refer to declared exchanges
-        self.connections.iter().try_for_each(|(ident, _)| {
-            ensure!(
-                self.exchanges.contains_key(ident),
-                "'{}' is not a declared exchange",
-                ident
-            );
-            Ok(())
-        })?;
+        // check that launch specs refer to declared processors
+        for key in self.launch.keys() {
+            if !self.processors.contains(key) {
+                let name = key.to_string();
+                let candidates: Vec<_> = self.processors.iter().map(Key::to_string).collect();
+                let suggestion = suggest(&name, candidates.iter().map(String::as_str));
+                report.error(
+                    file,
+                    "launch",
+                    &name,
+                    format!("'{}' is launched but not a declared processor", key),
+                    suggestion,
+                )?;
+            }
+        }
+
+        // check that connections refer to declared exchanges or modules
+        for key in self.connections.keys() {
+            if self.exchanges.contains_key(key) {
+                continue;
+            }
 
-        // list file bindings for modules
+            let name = key.to_string();
+            if key.is_ident() {
+                let candidates: Vec<_> = self.exchanges.keys().map(Key::to_string).collect();
+                let suggestion = suggest(&name, candidates.iter().map(String::as_str));
+                report.error(
+                    file,
+                    "connections",
+                    &name,
+                    format!("'{}' is not a declared exchange", key),
+                    suggestion,
+                )?;
+            } else {
+                let module = key.first_ident();
+                let modules = self.modules.iter().flat_map(|modules| modules.keys());
+                if !modules.clone().any(|ident| ident == module) {
+                    let suggestion = suggest(module.as_str(), modules.map(Ident::as_str));
+                    report.error(
+                        file,
+                        "connections",
+                        &name,
+                        format!(
+                            "'{}' does not refer to an exchange in a declared module",
+                            key
+                        ),
+                        suggestion,
+                    )?;
+                }
+            }
+        }
+
+        // connections to module exchanges are checked after the modules are flattened.
+        // undeclared modules are reported above.
+        let module_conn_keys: Vec<Key> = self
+            .connections
+            .keys()
+            .filter(|key| {
+                let module = key.first_ident();
+                !key.is_ident()
+                    && !self.exchanges.contains_key(*key)
+                    && self
+                        .modules
+                        .as_ref()
+                        .is_some_and(|modules| modules.contains_key(module))
+            })
+            .cloned()
+            .collect();
+
+        // list file bindings for modules where they are found.
+        // modules not found are reported below.
         let bindings: IndexMap<_, _> = self
             .modules
             .iter()
             .flatten()
-            .map(|(ident, module)| {
-                let path = module.abs_path().to_owned();
-                let dir = ident.clone().with_prefix(prefix.iter().cloned()).into_dir();
-                (path, dir)
+            .filter_map(|(ident, module)| {
+                let dir = ident.clone().with_prefix(stack.prefix()).into_dir();
+                let path = module.path.resolve(stack.search_path()).ok()?;
+                Some((dir, path))
             })
             .collect();
 
         // prepend prefix to identifiers in current graph
         let this = {
-            let prefix_iter = prefix.iter().cloned();
+            let prefix_iter = stack.prefix();
+
+            // scoped processor names are prefixed like exchanges
+            let proc_prefix: Vec<_> = match self.processor_namespace {
+                ProcessorNamespace::Flat => vec![],
+                ProcessorNamespace::Scoped => prefix_iter.clone().collect(),
+            };
+            let processors: IndexSet<Key> = self
+                .processors
+                .into_iter()
+                .map(|mut key| {
+                    key.prepend(proc_prefix.iter().cloned());
+                    key
+                })
+                .collect();
+
+            // record this module as the declaring module of processors
+            let module_dir = Dir::new(prefix_iter.clone().collect());
+            let declarations = processors
+                .iter()
+                .map(|key| (key.clone(), module_dir.clone()))
+                .collect();
 
-            // add prefix to each exchange name
+            // add prefix to each exchange name and resolve imported configurations
             let exchanges = self
                 .exchanges
                 .into_iter()
-                .map(|(ident, ex)| {
-                    let key = ident.with_prefix(prefix_iter.clone());
-                    (key, ex)
+                .map(|(mut key, ex)| {
+                    key.prepend(prefix_iter.clone());
+                    (key, ex.into_resolved())
                 })
                 .collect();
 
@@ -139,37 +617,170 @@ impl GraphConfig {
             let connections = self
                 .connections
                 .into_iter()
-                .map(|(ident, conn)| {
-                    let key = ident.with_prefix(prefix_iter.clone());
+                .map(|(mut key, mut conn)| {
+                    key.prepend(prefix_iter.clone());
+                    conn.prepend(proc_prefix.iter().cloned());
                     (key, conn)
                 })
                 .collect();
 
+            // prepend prefix to each launch spec like processors
+            let launch = self
+                .launch
+                .into_iter()
+                .map(|(mut key, launch)| {
+                    key.prepend(proc_prefix.iter().cloned());
+                    (key, launch)
+                })
+                .collect();
+
             GraphUnchecked {
                 bindings,
-                processors: self.processors,
+                processors,
                 exchanges,
                 connections,
+                launch,
+                declarations,
             }
         };
 
-        // recursively merge submodules
-        let subgraphs = self.modules.into_iter().flatten().map(|(ident, gconf)| {
+        // recursively flatten submodules
+        let mut subgraphs: Vec<(Ident, GraphUnchecked)> = vec![];
+
+        for (ident, module) in self.modules.into_iter().flatten() {
+            let path = match module.path.resolve(stack.search_path()) {
+                Ok(path) => path,
+                Err(err) => {
+                    report.module_error(file, ident.as_str(), err)?;
+                    continue;
+                }
+            };
+
+            // enter the module file. missing files and include cycles are refused
+            if let Err(err) = stack.push(ident.clone(), &path) {
+                report.module_error(file, ident.as_str(), err)?;
+                continue;
+            }
+
             // flatten subgraph
-            prefix.push(ident);
-            let graph = gconf.take().flatten_recursive(prefix)?;
-            prefix.pop();
-            Ok(graph)
-        });
+            let dir = Dir::new(stack.prefix().collect());
+            let result = module
+                .load(&path, &dir)
+                .and_then(|gconf| gconf.flatten_with(stack, report));
+            stack.pop();
 
-        let graph = chain!([Ok(this)], subgraphs)
-            .reduce(|lhs, rhs| -> Result<_> {
-                let lhs = lhs?;
-                let rhs = rhs?;
-                let merged = lhs.merge_unchecked(rhs);
-                Ok(merged)
-            })
-            .unwrap()?;
+            match result {
+                Ok(subgraph) => subgraphs.push((ident, subgraph)),
+                Err(err) => report.module_error(file, ident.as_str(), err)?,
+            }
+        }
+
+        // check that flat processor names are not declared by sibling modules
+        // unless the current graph declares and shares it
+        {
+            let mut owners: IndexMap<&Key, &Ident> = IndexMap::new();
+
+            for (ident, subgraph) in &subgraphs {
+                let flat_procs = subgraph
+                    .processors
+                    .iter()
+                    .filter(|proc| proc.is_ident() && !this.processors.contains(*proc));
+
+                for proc in flat_procs {
+                    let Some(other) = owners.insert(proc, ident) else {
+                        continue;
+                    };
+                    let other_key = other.clone().with_prefix(stack.prefix());
+                    let ident_key = ident.clone().with_prefix(stack.prefix());
+                    report.error(
+                        file,
+                        "modules",
+                        ident.as_str(),
+                        format!(
+                            "processor '{}' is declared by both modules '{}' and '{}'. \
+                             Declare it in the including graph to share it, \
+                             or use the scoped processor namespace in the modules",
+                            proc, other_key, ident_key
+                        ),
+                        None,
+                    )?;
+                }
+            }
+        }
+
+        // check that exchanges and scoped processors are declared only once.
+        // a key written in this graph may coincide with a name in a module,
+        // e.g. "cam/X" in this graph and "X" in the module "cam".
+        {
+            let this_origin = match Dir::new(stack.prefix().collect()) {
+                dir if dir.is_root() => "the root graph".to_string(),
+                dir => format!("module '{}'", dir),
+            };
+            let declared = |graph: &GraphUnchecked| -> Vec<(&'static str, Key)> {
+                let exchanges = graph.exchanges.keys().map(|key| ("exchange", key.clone()));
+                let procs = graph
+                    .processors
+                    .iter()
+                    .filter(|proc| !proc.is_ident())
+                    .map(|proc| ("processor", proc.clone()));
+                exchanges.chain(procs).collect()
+            };
+
+            let mut owners: IndexMap<(&str, Key), String> = declared(&this)
+                .into_iter()
+                .map(|name| (name, this_origin.clone()))
+                .collect();
+
+            for (ident, subgraph) in &subgraphs {
+                let origin = format!("module '{}'", ident.clone().with_prefix(stack.prefix()));
+
+                for (kind, key) in declared(subgraph) {
+                    let Some(other) = owners.insert((kind, key.clone()), origin.clone()) else {
+                        continue;
+                    };
+                    report.error(
+                        file,
+                        "modules",
+                        ident.as_str(),
+                        format!(
+                            "{} '{}' is declared by both {} and {}",
+                            kind, key, other, origin
+                        ),
+                        None,
+                    )?;
+                }
+            }
+        }
+
+        // merge submodules
+        let graph = subgraphs
+            .into_iter()
+            .fold(this, |lhs, (_, rhs)| lhs.merge_unchecked(rhs));
+
+        // suggest exchanges of the modules as written in this graph
+        let prefix: String = stack.prefix().map(|ident| format!("{}/", ident)).collect();
+        let module_exchanges: Vec<String> = graph
+            .exchanges
+            .keys()
+            .filter_map(|key| Some(key.to_string().strip_prefix(&prefix)?.to_string()))
+            .collect();
+
+        for key in module_conn_keys {
+            let mut full_key = key.clone();
+            full_key.prepend(stack.prefix());
+
+            if !graph.exchanges.contains_key(&full_key) {
+                let name = key.to_string();
+                let suggestion = suggest(&name, module_exchanges.iter().map(String::as_str));
+                report.error(
+                    file,
+                    "connections",
+                    &name,
+                    format!("'{}' is not an exchange declared in modules", full_key),
+                    suggestion,
+                )?;
+            }
+        }
 
         Ok(graph)
     }
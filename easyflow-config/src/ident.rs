@@ -1,6 +1,11 @@
 use crate::key::Key;
 use anyhow::Result;
 use itertools::chain;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Schema, SchemaObject, StringValidation},
+    JsonSchema,
+};
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use std::{borrow::Cow, fmt, fmt::Display, hash::Hash, str::FromStr, string::ToString};
 
@@ -67,3 +72,26 @@ impl<'a> Deserialize<'a> for Ident {
         Ident::new(&name).ok_or_else(|| D::Error::custom(format!("invalid name {}", name)))
     }
 }
+
+impl JsonSchema for Ident {
+    fn schema_name() -> String {
+        "Ident".into()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        string_schema(r"^[A-Za-z0-9_-]+$")
+    }
+}
+
+/// Create the schema for strings matching the pattern.
+pub(crate) fn string_schema(pattern: &str) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        string: Some(Box::new(StringValidation {
+            pattern: Some(pattern.into()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
@@ -1,6 +1,10 @@
-use crate::{dir::Dir, ident::Ident};
+use crate::{
+    dir::Dir,
+    ident::{string_schema, Ident},
+};
 use anyhow::Result;
 use itertools::{chain, Itertools as _};
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use std::{fmt, fmt::Display, hash::Hash, str::FromStr, string::ToString};
 
@@ -24,6 +28,17 @@ impl Key {
         Dir(self.0)
     }
 
+    /// Obtain the directory that the key is declared in, which is the
+    /// key without the last identifier.
+    pub fn parent(&self) -> Dir {
+        Dir(self.0[..self.0.len() - 1].to_vec())
+    }
+
+    /// Obtain the first identifier in the key.
+    pub fn first_ident(&self) -> &Ident {
+        self.0.first().as_ref().unwrap()
+    }
+
     /// Obtain the last identifier in the key.
     pub fn last_ident(&self) -> &Ident {
         self.0.last().as_ref().unwrap()
@@ -98,3 +113,13 @@ impl<'a> Deserialize<'a> for Key {
         Ok(Key::new(idents).unwrap())
     }
 }
+
+impl JsonSchema for Key {
+    fn schema_name() -> String {
+        "Key".into()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        string_schema(r"^[A-Za-z0-9_-]+(/[A-Za-z0-9_-]+)*$")
+    }
+}
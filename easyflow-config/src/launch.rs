@@ -0,0 +1,83 @@
+use indexmap::IndexMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_loader::AbsPathBuf;
+use std::path::{self, PathBuf};
+
+/// The specification to start a processor program.
+///
+/// ```json5
+/// "launch": {
+///     "merger": {
+///         "command": "cargo",
+///         "args": ["run", "--bin", "merger"],
+///         "env": { "RUST_LOG": "info" },
+///         "restart": "on_failure",
+///     },
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Launch {
+    /// The program to be executed.
+    pub command: String,
+    /// Arguments passed to the program.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Additional environment variables.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub env: IndexMap<String, String>,
+    /// The working directory of the program.
+    ///
+    /// A relative path is resolved against the file declaring it. It
+    /// defaults to the directory of that file.
+    #[schemars(with = "Option<PathBuf>")]
+    #[serde(
+        default = "config_dir",
+        deserialize_with = "deserialize_working_dir",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub working_dir: Option<PathBuf>,
+    /// Whether to start the program again after it exits.
+    #[serde(default, skip_serializing_if = "RestartPolicy::is_never")]
+    pub restart: RestartPolicy,
+}
+
+/// The policy to restart a processor program after it exits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartPolicy {
+    /// Do not restart.
+    #[default]
+    Never,
+    /// Restart if the program exits with a failure status.
+    OnFailure,
+    /// Restart whenever the program exits.
+    Always,
+}
+
+impl RestartPolicy {
+    /// Return true if the program is never restarted.
+    pub fn is_never(&self) -> bool {
+        *self == Self::Never
+    }
+}
+
+/// The absolute directory of the configuration file being
+/// deserialized, or `None` if it is unknown.
+fn config_dir() -> Option<PathBuf> {
+    resolve_path(PathBuf::new())
+}
+
+fn deserialize_working_dir<'de, D>(deserializer: D) -> Result<Option<PathBuf>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let path = PathBuf::deserialize(deserializer)?;
+    Ok(resolve_path(path))
+}
+
+/// Rebase the path against the file being deserialized and make it absolute.
+fn resolve_path(path: PathBuf) -> Option<PathBuf> {
+    let path = AbsPathBuf::try_from(path).ok()?.into_inner();
+    path::absolute(path).ok()
+}
@@ -1,15 +1,31 @@
 mod connection;
+mod diagnostic;
 mod dir;
 mod graph;
 mod ident;
 mod into_ident;
 mod into_key;
 mod key;
+mod launch;
+mod lint;
+mod loader;
+mod migrate;
+mod module;
+mod overlay;
+mod schema;
+mod subst;
 
 pub use connection::*;
+pub use diagnostic::*;
 pub use dir::*;
 pub use graph::*;
 pub use ident::*;
 pub use into_ident::*;
 pub use into_key::*;
 pub use key::*;
+pub use launch::*;
+pub use lint::*;
+pub use migrate::*;
+pub use module::*;
+pub use overlay::*;
+pub use schema::*;
@@ -0,0 +1,147 @@
+use crate::{diagnostic::Report, GraphUnchecked, Key};
+use anyhow::Result;
+use indexmap::IndexMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+    path::Path,
+};
+
+/// A check for suspicious but valid dataflows.
+///
+/// The level of each lint is configured in the `lints` section of the
+/// root dataflow file. Lints are warned by default.
+///
+/// ```json5
+/// "lints": {
+///     "no_consumer": "allow",
+///     "isolated_processor": "deny",
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Lint {
+    /// An exchange that is not connected to any processor.
+    DanglingExchange,
+    /// An exchange that no processor sends to.
+    NoProducer,
+    /// An exchange that no processor receives from.
+    NoConsumer,
+    /// A processor in the root graph that is not connected to any exchange.
+    IsolatedProcessor,
+    /// A processor declared by a module that is not connected to any exchange.
+    UnusedModuleProcessor,
+}
+
+impl Lint {
+    /// All available lints.
+    pub const ALL: [Lint; 5] = [
+        Self::DanglingExchange,
+        Self::NoProducer,
+        Self::NoConsumer,
+        Self::IsolatedProcessor,
+        Self::UnusedModuleProcessor,
+    ];
+
+    /// The name used in dataflow files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::DanglingExchange => "dangling_exchange",
+            Self::NoProducer => "no_producer",
+            Self::NoConsumer => "no_consumer",
+            Self::IsolatedProcessor => "isolated_processor",
+            Self::UnusedModuleProcessor => "unused_module_processor",
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.name().fmt(f)
+    }
+}
+
+/// What to do when a [Lint] fires.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LintLevel {
+    /// Ignore the lint.
+    Allow,
+    /// Report a warning.
+    #[default]
+    Warn,
+    /// Report an error and refuse to load the dataflow.
+    Deny,
+}
+
+impl GraphUnchecked {
+    /// Run every lint and report the problems to `report`.
+    pub(crate) fn lint_with(
+        &self,
+        levels: &IndexMap<Lint, LintLevel>,
+        report: &mut Report,
+    ) -> Result<()> {
+        let level = |lint: Lint| levels.get(&lint).copied().unwrap_or_default();
+
+        for key in self.exchanges.keys() {
+            let conn = self.connections.get(key);
+            let has_producer = conn.is_some_and(|conn| conn.sink_iter().next().is_some());
+            let has_consumer = conn.is_some_and(|conn| conn.source_iter().next().is_some());
+
+            let (lint, message) = match (has_producer, has_consumer) {
+                (true, true) => continue,
+                (false, false) => (
+                    Lint::DanglingExchange,
+                    format!("exchange '{}' is not connected to any processor", key),
+                ),
+                (false, true) => (
+                    Lint::NoProducer,
+                    format!("no processor sends to exchange '{}'", key),
+                ),
+                (true, false) => (
+                    Lint::NoConsumer,
+                    format!("no processor receives from exchange '{}'", key),
+                ),
+            };
+
+            // the exchange is written by its local name in a module
+            let (file, name) = match self.bindings.get(&key.parent()) {
+                Some(file) => (Some(file.as_path()), key.last_ident().to_string()),
+                None => (None, key.to_string()),
+            };
+            report.lint(file, "exchanges", &name, lint, level(lint), message)?;
+        }
+
+        let connected: HashSet<&Key> = self
+            .connections
+            .values()
+            .flat_map(|conn| conn.sink_iter().chain(conn.source_iter()))
+            .collect();
+
+        for key in &self.processors {
+            if connected.contains(key) {
+                continue;
+            }
+
+            let module_file: Option<&Path> = self
+                .declarations
+                .get(key)
+                .and_then(|dir| self.bindings.get(dir))
+                .map(|file| file.as_path());
+            let (lint, file, name) = match module_file {
+                Some(file) => (
+                    Lint::UnusedModuleProcessor,
+                    Some(file),
+                    key.last_ident().to_string(),
+                ),
+                None => (Lint::IsolatedProcessor, None, key.to_string()),
+            };
+            let message = format!("processor '{}' is not connected to any exchange", key);
+            report.lint(file, "processors", &name, lint, level(lint), message)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{
+    graph::GraphConfig,
+    ident::Ident,
+    key::Key,
+    migrate::migrate,
+    overlay::{merge_patch, Overlay},
+    subst::{Substitution, Variables},
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use easyflow_link::{
+    format::{Format, FormatDumper},
+    Config as Exchange,
+};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use serde_loader::file::{FileLoader, FilePath};
+use std::{
+    cell::RefCell,
+    path::{self, Path},
+};
+
+thread_local! {
+    static VARIABLES: RefCell<Variables> = RefCell::new(Variables::default());
+    static BASE_EXCHANGES: RefCell<IndexMap<Key, Exchange>> = RefCell::new(IndexMap::new());
+}
+
+/// Open a graph configuration file and substitute variables into it.
+///
+/// The file format is chosen by the file extension. See [Format::from_path].
+pub(crate) fn load_graph(path: &Path, vars: Variables) -> Result<GraphConfig> {
+    let prev_vars = VARIABLES.with(|cell| cell.replace(vars));
+    let result = FilePath::<GraphConfig, FormatDumper, GraphLoader>::open_and_take(path);
+    VARIABLES.with(|cell| cell.replace(prev_vars));
+
+    let config = result?;
+    Ok(config)
+}
+
+/// The file loader that upgrades the format version and substitutes
+/// variables before deserializing the graph configuration.
+///
+/// It is run within [FilePath::open] so that relative paths in the
+/// configuration are resolved against the configuration file.
+struct GraphLoader {
+    _private: [u8; 0],
+}
+
+impl FileLoader<GraphConfig> for GraphLoader {
+    type Error = anyhow::Error;
+
+    fn load<P>(p: P) -> Result<GraphConfig, Self::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = p.as_ref();
+        let mut value: Value = Format::load(path)?;
+        migrate(&mut value)?;
+
+        VARIABLES.with(|cell| -> Result<_> {
+            let mut vars = cell.borrow_mut();
+            if let Some(dir) = path::absolute(path)?.parent() {
+                vars.config_dir = dir.to_owned();
+            }
+            let mut subst = Substitution::new(&vars);
+
+            // substitute exchange configurations, launch specs and module arguments
+            for section in ["exchanges", "launch"] {
+                if let Some(value) = value.get_mut(section) {
+                    subst.apply(value).map_err(|err| anyhow!("{:#}", err))?;
+                }
+            }
+            if let Some(Value::Object(modules)) = value.get_mut("modules") {
+                modules
+                    .values_mut()
+                    .filter_map(|module| module.get_mut("args"))
+                    .try_for_each(|args| subst.apply(args))
+                    .map_err(|err| anyhow!("{:#}", err))?;
+            }
+
+            let unused: Vec<_> = subst.unused_args().map(Ident::as_str).collect();
+            ensure!(
+                unused.is_empty(),
+                "unused module arguments: {}",
+                unused.join(", ")
+            );
+            Ok(())
+        })?;
+
+        let config = GraphConfig::deserialize(value)?;
+        Ok(config)
+    }
+}
+
+/// Open an overlay file to be applied on top of the `exchanges`.
+pub(crate) fn load_overlay(path: &Path, exchanges: &IndexMap<Key, Exchange>) -> Result<Overlay> {
+    let prev = BASE_EXCHANGES.with(|cell| cell.replace(exchanges.clone()));
+    let result = FilePath::<Overlay, FormatDumper, OverlayLoader>::open_and_take(path);
+    BASE_EXCHANGES.with(|cell| cell.replace(prev));
+
+    let overlay = result?;
+    Ok(overlay)
+}
+
+/// The file loader that substitutes variables and merges patches into
+/// exchange configurations before deserializing the overlay.
+///
+/// Patched configurations are deserialized within [FilePath::open],
+/// so that relative paths in patches are resolved against the overlay
+/// file.
+struct OverlayLoader {
+    _private: [u8; 0],
+}
+
+impl FileLoader<Overlay> for OverlayLoader {
+    type Error = anyhow::Error;
+
+    fn load<P>(p: P) -> Result<Overlay, Self::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = p.as_ref();
+        let mut value: Value = Format::load(path)?;
+
+        // substitute exchange configurations and patches
+        let vars = Variables {
+            config_dir: path::absolute(path)?
+                .parent()
+                .map(Path::to_owned)
+                .unwrap_or_default(),
+            ..Variables::default()
+        };
+        let mut subst = Substitution::new(&vars);
+        for section in ["exchanges", "patches"] {
+            if let Some(value) = value.get_mut(section) {
+                subst.apply(value).map_err(|err| anyhow!("{:#}", err))?;
+            }
+        }
+
+        // merge patches into exchange configurations
+        let patches = match value.as_object_mut().and_then(|doc| doc.remove("patches")) {
+            Some(Value::Object(patches)) => patches,
+            Some(_) => bail!("patches must be a map from exchange keys to patches"),
+            None => Map::new(),
+        };
+        if !patches.is_empty() {
+            let Some(doc) = value.as_object_mut() else {
+                bail!("the overlay must be a map");
+            };
+            let exchanges = doc
+                .entry("exchanges")
+                .or_insert_with(|| Value::Object(Map::new()));
+
+            BASE_EXCHANGES.with(|cell| -> Result<_> {
+                let base = cell.borrow();
+
+                for (name, patch) in patches {
+                    let key: Key = name.parse().map_err(|err| anyhow!("{}", err))?;
+                    let target = match exchanges.get_mut(&name) {
+                        Some(target) => target,
+                        None => {
+                            let Some(orig) = base.get(&key) else {
+                                bail!("unable to patch exchange '{}' that does not exist", key);
+                            };
+                            exchanges[&name] = serde_json::to_value(orig)?;
+                            &mut exchanges[&name]
+                        }
+                    };
+                    merge_patch(target, patch);
+                }
+                Ok(())
+            })?;
+        }
+
+        let overlay = Overlay::deserialize(value)?;
+        Ok(overlay)
+    }
+}
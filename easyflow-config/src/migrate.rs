@@ -0,0 +1,121 @@
+use anyhow::{bail, ensure, Context, Result};
+use easyflow_link::format::Format;
+use serde_json::Value;
+use serde_semver::semver::{Version as Semver, VersionReq};
+use std::{fs, path::Path};
+
+/// The format version of newly written graph configurations.
+pub const CURRENT_VERSION: &str = "0.2.0";
+
+/// An upgrade step from a range of older format versions.
+struct Migration {
+    /// The semver requirement on versions to be upgraded.
+    from: &'static str,
+    /// The version after the upgrade.
+    to: &'static str,
+    apply: fn(&mut Value),
+}
+
+/// Upgrade steps ordered from the oldest version.
+const MIGRATIONS: &[Migration] = &[
+    // 0.2.0 introduces ${name} placeholders. They were substituted
+    // in 0.1.0 files before the version was bumped, so `${` already
+    // means a placeholder there and only the version changes.
+    Migration {
+        from: "^0.1",
+        to: "0.2.0",
+        apply: |_| {},
+    },
+];
+
+/// Get the format version of a graph configuration document.
+pub fn document_version(doc: &Value) -> Result<Semver> {
+    let Some(version) = doc.get("version") else {
+        bail!("the format version is missing");
+    };
+    let Some(version) = version.as_str() else {
+        bail!("the format version must be a string");
+    };
+    Semver::parse(version).with_context(|| format!("invalid format version '{}'", version))
+}
+
+/// Upgrade a graph configuration document to the [current
+/// version](CURRENT_VERSION) in place.
+///
+/// It returns `true` if the document is modified, or `false` if it
+/// is already in the current version. The version field is set to
+/// the current version after upgrade.
+pub fn migrate(doc: &mut Value) -> Result<bool> {
+    let current = Semver::parse(CURRENT_VERSION).unwrap();
+    let current_req = VersionReq::parse(CURRENT_VERSION).unwrap();
+    let mut version = document_version(doc)?;
+    let mut modified = false;
+
+    while !current_req.matches(&version) {
+        ensure!(
+            version < current,
+            "the format version {} is newer than the supported version {}",
+            version,
+            CURRENT_VERSION
+        );
+
+        let Some(migration) = MIGRATIONS
+            .iter()
+            .find(|migration| VersionReq::parse(migration.from).unwrap().matches(&version))
+        else {
+            bail!("the format version {} is no longer supported", version);
+        };
+
+        (migration.apply)(doc);
+        version = Semver::parse(migration.to).unwrap();
+        modified = true;
+    }
+
+    if modified {
+        doc["version"] = Value::String(CURRENT_VERSION.to_string());
+    }
+    Ok(modified)
+}
+
+/// Upgrade a graph configuration file to the [current
+/// version](CURRENT_VERSION).
+///
+/// The file is rewritten in the same format only if it is upgraded,
+/// and it returns `true` in that case. If the upgrade only changes
+/// the version, only the version in the original text is replaced,
+/// keeping comments and formatting. Otherwise the whole document is
+/// rewritten without comments. Modules are not migrated.
+pub fn migrate_file<P>(path: P) -> Result<bool>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let format = Format::from_path(path);
+    let orig = fs::read_to_string(path)?;
+    let mut doc: Value = format.parse(&orig)?;
+    let version = document_version(&doc)?.to_string();
+
+    if !migrate(&mut doc)? {
+        return Ok(false);
+    }
+
+    let text = match replace_version(format, &orig, &version, &doc) {
+        Some(text) => text,
+        None => format.to_string(&doc)?,
+    };
+    fs::write(path, text)?;
+    Ok(true)
+}
+
+/// Replace the `version` in the original text by the current version,
+/// and return the new text if it parses to the upgraded document.
+fn replace_version(format: Format, orig: &str, version: &str, doc: &Value) -> Option<String> {
+    // Try each occurrence of the old version, since it may also occur
+    // in comments or other values.
+    orig.match_indices(version).find_map(|(start, _)| {
+        let end = start + version.len();
+        let text = format!("{}{}{}", &orig[..start], CURRENT_VERSION, &orig[end..]);
+        let parsed: Value = format.parse(&text).ok()?;
+        (&parsed == doc).then_some(text)
+    })
+}
@@ -0,0 +1,358 @@
+use crate::{dir::Dir, graph::GraphConfig, ident::Ident, loader::load_graph, subst::Variables};
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use serde_loader::AbsPathBuf;
+use std::{
+    env,
+    error::Error as StdError,
+    fmt,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The environment variable listing directories to search for
+/// package modules, separated like `PATH`.
+pub const MODULE_PATH_ENV: &str = "EASYFLOW_MODULE_PATH";
+
+/// A module entry in a graph configuration.
+///
+/// It is either a file path, or a path with arguments to be
+/// substituted into `${name}` placeholders in the module.
+///
+/// A path starting with `@` refers to a package module, which is
+/// searched in the module search path instead of the directory of the
+/// including file. For example, `@sensors/camera.json5` is found at
+/// `sensors/camera.json5` in the first directory containing it. See
+/// [IncludeStack::with_search_path].
+///
+/// ```json5
+/// "modules": {
+///     "cam0": "camera.json5",
+///     "cam1": {
+///         "path": "camera.json5",
+///         "args": { "index": 1 },
+///     },
+///     "lidar": "@sensors/lidar.json5",
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "ModuleRepr", into = "ModuleRepr")]
+pub struct Module {
+    /// The path to the module file.
+    pub path: ModulePath,
+    /// Values for `${name}` placeholders in the module.
+    pub args: IndexMap<Ident, Value>,
+}
+
+impl Module {
+    /// Open the module file found at `abs_path` and placed at the
+    /// namespace `dir`, and substitute variables into its
+    /// configuration. The file is found by [ModulePath::resolve].
+    ///
+    /// It fails if the file has a placeholder that refers to an
+    /// undefined variable, or an argument is not used by any
+    /// placeholder.
+    pub fn load(&self, abs_path: &Path, dir: &Dir) -> Result<GraphConfig> {
+        let vars = Variables {
+            args: self.args.clone(),
+            module: dir.clone(),
+            ..Variables::default()
+        };
+        let config = load_graph(abs_path, vars)
+            .with_context(|| format!("unable to load module '{}'", abs_path.display()))?;
+        Ok(config)
+    }
+}
+
+impl From<ModulePath> for Module {
+    fn from(path: ModulePath) -> Self {
+        Self {
+            path,
+            args: IndexMap::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum ModuleRepr {
+    /// The path to the module file.
+    Path(ModulePath),
+    /// The path to the module file with arguments.
+    Template {
+        /// The path to the module file.
+        path: ModulePath,
+        /// Values for `${name}` placeholders in the module.
+        #[serde(default)]
+        args: IndexMap<Ident, Value>,
+    },
+}
+
+impl JsonSchema for Module {
+    fn schema_name() -> String {
+        "Module".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        ModuleRepr::json_schema(gen)
+    }
+}
+
+impl From<ModuleRepr> for Module {
+    fn from(repr: ModuleRepr) -> Self {
+        match repr {
+            ModuleRepr::Path(path) => path.into(),
+            ModuleRepr::Template { path, args } => Self { path, args },
+        }
+    }
+}
+
+impl From<Module> for ModuleRepr {
+    fn from(module: Module) -> Self {
+        let Module { path, args } = module;
+        if args.is_empty() {
+            Self::Path(path)
+        } else {
+            Self::Template { path, args }
+        }
+    }
+}
+
+/// The path to a graph configuration file included as a module.
+///
+/// The path is relative to the file where it is written, or to a
+/// directory in the module search path if it starts with `@`. Unlike
+/// [Json5Path](serde_loader::Json5Path), the file is not opened during
+/// deserialization. It is loaded on demand when the graph is flattened,
+/// so that cyclic includes can be detected.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModulePath {
+    ref_path: PathBuf,
+    abs_path: PathBuf,
+}
+
+impl ModulePath {
+    /// Create from a path relative to the file being deserialized.
+    pub fn new<P>(path: P) -> Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let ref_path = path.into();
+        let abs_path = if is_package_path(&ref_path) {
+            ref_path.clone()
+        } else {
+            AbsPathBuf::try_from(&ref_path)?.into_inner()
+        };
+        Ok(Self { ref_path, abs_path })
+    }
+
+    /// Get the path as written in the configuration.
+    pub fn ref_path(&self) -> &Path {
+        &self.ref_path
+    }
+
+    /// Get the path resolved against the including file.
+    ///
+    /// It is the path as written for a package module. Use
+    /// [resolve](ModulePath::resolve) to find the file instead.
+    pub fn abs_path(&self) -> &Path {
+        &self.abs_path
+    }
+
+    /// Get the path within the package directories if it refers to a
+    /// package module like `@sensors/camera.json5`.
+    pub fn package_path(&self) -> Option<&Path> {
+        self.ref_path.to_str()?.strip_prefix('@').map(Path::new)
+    }
+
+    /// Find the module file.
+    ///
+    /// A package module is searched in the directories of
+    /// `search_path` in order, and the first existing file is
+    /// returned. Other paths are resolved against the including
+    /// file.
+    pub fn resolve(&self, search_path: &[PathBuf]) -> Result<PathBuf> {
+        let Some(package_path) = self.package_path() else {
+            return Ok(self.abs_path.clone());
+        };
+
+        let found = search_path
+            .iter()
+            .map(|dir| dir.join(package_path))
+            .find(|path| path.is_file());
+        let Some(path) = found else {
+            let dirs: Vec<_> = search_path
+                .iter()
+                .map(|dir| dir.display().to_string())
+                .collect();
+            bail!(
+                "package module '{}' is not found in the module search path [{}]",
+                self.ref_path.display(),
+                dirs.join(", ")
+            );
+        };
+        Ok(path)
+    }
+}
+
+fn is_package_path(path: &Path) -> bool {
+    path.to_str().is_some_and(|path| path.starts_with('@'))
+}
+
+impl Serialize for ModulePath {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.ref_path.serialize(serializer)
+    }
+}
+
+impl JsonSchema for ModulePath {
+    fn schema_name() -> String {
+        PathBuf::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        PathBuf::json_schema(gen)
+    }
+}
+
+impl<'a> Deserialize<'a> for ModulePath {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        let path = PathBuf::deserialize(deserializer)?;
+        Self::new(path).map_err(D::Error::custom)
+    }
+}
+
+/// A module being flattened and the file it is loaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Include {
+    pub ident: Ident,
+    pub path: PathBuf,
+}
+
+/// The chain of modules being flattened, from the outermost to the
+/// innermost one, and the directories to search for package modules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludeStack {
+    includes: Vec<Include>,
+    search_path: Vec<PathBuf>,
+}
+
+impl IncludeStack {
+    /// Create an empty stack.
+    ///
+    /// Package modules are searched in the directories listed in the
+    /// [MODULE_PATH_ENV] environment variable.
+    pub fn new() -> Self {
+        Self::with_search_path(Vec::<PathBuf>::new())
+    }
+
+    /// Create an empty stack that searches package modules in the
+    /// directories of `search_path`, and then in the directories listed
+    /// in the [MODULE_PATH_ENV] environment variable.
+    pub fn with_search_path<I, P>(search_path: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        let env_path = env::var_os(MODULE_PATH_ENV);
+        let env_dirs = env_path
+            .iter()
+            .flat_map(env::split_paths)
+            .filter(|dir| !dir.as_os_str().is_empty());
+        let search_path = search_path
+            .into_iter()
+            .map(Into::into)
+            .chain(env_dirs)
+            .collect();
+
+        Self {
+            includes: vec![],
+            search_path,
+        }
+    }
+
+    /// Get the directories to search for package modules in order.
+    pub fn search_path(&self) -> &[PathBuf] {
+        &self.search_path
+    }
+
+    /// Iterate over module identifiers from the outermost one.
+    ///
+    /// It is the prefix prepended to the names declared in the
+    /// innermost module.
+    pub fn prefix(&self) -> impl Iterator<Item = Ident> + Clone + '_ {
+        self.includes.iter().map(|include| include.ident.clone())
+    }
+
+    /// Enter a module loaded from `path`.
+    ///
+    /// It fails if the file cannot be found, or returns an
+    /// [IncludeCycle] error if the file is already being flattened by
+    /// an outer module.
+    pub fn push(&mut self, ident: Ident, path: &Path) -> Result<()> {
+        // compare canonical paths so that "a/../b.json5" and "b.json5" are the same file
+        let path = fs::canonicalize(path)
+            .with_context(|| format!("unable to load module '{}'", path.display()))?;
+        let include = Include { ident, path };
+
+        if let Some(start) = self
+            .includes
+            .iter()
+            .position(|inc| inc.path == include.path)
+        {
+            let mut chain = self.includes[start..].to_vec();
+            chain.push(include);
+            return Err(IncludeCycle { chain }.into());
+        }
+
+        self.includes.push(include);
+        Ok(())
+    }
+
+    /// Leave the innermost module.
+    pub fn pop(&mut self) -> Option<Include> {
+        self.includes.pop()
+    }
+
+    /// Get the included modules from the outermost one.
+    pub fn includes(&self) -> &[Include] {
+        &self.includes
+    }
+}
+
+impl Default for IncludeStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The error returned when a module includes itself, directly or
+/// through other modules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludeCycle {
+    /// The include chain that starts and ends at the same file.
+    pub chain: Vec<Include>,
+}
+
+impl Display for IncludeCycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let chain: Vec<_> = self
+            .chain
+            .iter()
+            .map(|include| format!("`{}` ({})", include.ident, include.path.display()))
+            .collect();
+        write!(f, "cyclic module include: {}", chain.join(" -> "))
+    }
+}
+
+impl StdError for IncludeCycle {}
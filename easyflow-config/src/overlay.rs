@@ -0,0 +1,155 @@
+use crate::{graph::Version, loader::load_overlay, Connection, Dir, GraphUnchecked, Key};
+use anyhow::{bail, ensure, Context, Result};
+use easyflow_link::Config as Exchange;
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// A file that modifies a flattened graph.
+///
+/// Overlays adapt a graph to another environment without copying it,
+/// for example to replace network exchanges by files in CI. An
+/// overlay can
+///
+/// - `exchanges`: Replace exchange configurations by keys, or declare new exchanges.
+/// - `patches`: Change some fields of exchange configurations. It is
+///   applied as a [JSON merge patch](https://www.rfc-editor.org/rfc/rfc7396),
+///   where a `null` value removes the field.
+/// - `processors`: Declare additional processors.
+/// - `connections`: Connect processors to exchanges in addition to existing connections.
+/// - `disabled_connections`: Remove processors from existing connections.
+///
+/// Keys refer to the flattened graph, such as `camera/VIDEO` for an
+/// exchange declared by the module `camera`. Relative paths and
+/// `${config_dir}` in exchange configurations and patches refer to
+/// the directory of the overlay file.
+///
+/// ```json5
+/// {
+///     "version": "0.2.0",
+///     "exchanges": {
+///         "camera/VIDEO": { "type": "file", "dir": "recordings/video" },
+///     },
+///     "patches": {
+///         "lidar/POINTS": { "key": "ci/points" },
+///     },
+///     "disabled_connections": {
+///         "OUTPUT": { ">": ["uploader"] },
+///     },
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Overlay {
+    /// Format version
+    pub version: Version,
+    /// Processors to be declared.
+    #[serde(default)]
+    pub processors: IndexSet<Key>,
+    /// Exchanges to be replaced or declared. Patches are merged into
+    /// this list when the overlay is loaded.
+    #[serde(default)]
+    pub exchanges: IndexMap<Key, Exchange>,
+    /// Connections to be added.
+    #[serde(default)]
+    pub connections: IndexMap<Key, Connection>,
+    /// Connections to be removed.
+    #[serde(default)]
+    pub disabled_connections: IndexMap<Key, Connection>,
+}
+
+impl Overlay {
+    /// Open an overlay file to be applied on top of the `exchanges`.
+    ///
+    /// Exchange configurations that `patches` refer to are looked up
+    /// in `exchanges`, and the patched configurations are placed in
+    /// [exchanges](Overlay::exchanges).
+    pub fn open<P>(path: P, exchanges: &IndexMap<Key, Exchange>) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        load_overlay(path, exchanges)
+            .with_context(|| format!("unable to load overlay '{}'", path.display()))
+    }
+
+    /// Modify the graph by the overlay.
+    pub fn apply(self, graph: &mut GraphUnchecked) -> Result<()> {
+        let Self {
+            processors,
+            exchanges,
+            connections,
+            disabled_connections,
+            ..
+        } = self;
+
+        // processors added by overlays are owned by the root graph
+        for key in processors {
+            graph
+                .declarations
+                .entry(key.clone())
+                .or_insert(Dir::new(vec![]));
+            graph.processors.insert(key);
+        }
+
+        graph.exchanges.extend(exchanges);
+
+        for (key, conn) in connections {
+            match graph.connections.get_mut(&key) {
+                Some(orig) => orig.merge_with(conn),
+                None => {
+                    graph.connections.insert(key, conn);
+                }
+            }
+        }
+
+        for (key, disabled) in disabled_connections {
+            let Some(conn) = graph.connections.get_mut(&key) else {
+                bail!("unable to disable connection '{}' that does not exist", key);
+            };
+
+            for (side, procs, disabled) in [
+                ("<", &mut conn.sink, disabled.sink),
+                (">", &mut conn.source, disabled.source),
+            ] {
+                for proc in disabled.into_iter().flatten() {
+                    let removed = procs
+                        .as_mut()
+                        .is_some_and(|procs| procs.shift_remove(&proc));
+                    ensure!(
+                        removed,
+                        "unable to disable connection '{}' that does not have '{}' in '{}'",
+                        key,
+                        proc,
+                        side
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Merge the `patch` into the `target` following
+/// [RFC 7396](https://www.rfc-editor.org/rfc/rfc7396).
+pub(crate) fn merge_patch(target: &mut Value, patch: Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    let Value::Object(target) = target else {
+        unreachable!();
+    };
+
+    for (name, value) in patch {
+        if value.is_null() {
+            target.remove(&name);
+        } else {
+            merge_patch(target.entry(name).or_insert(Value::Null), value);
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use crate::graph::GraphConfig;
+use schemars::{schema::RootSchema, schema_for};
+
+/// Generate the JSON Schema of graph configuration files.
+///
+/// The schema can be used by editors to validate and autocomplete
+/// dataflow files. Exchange types follow the cargo features enabled
+/// on the easyflow-link crate.
+pub fn graph_schema() -> RootSchema {
+    schema_for!(GraphConfig)
+}
@@ -0,0 +1,205 @@
+use crate::{dir::Dir, ident::Ident};
+use anyhow::{bail, Context, Result};
+use indexmap::{IndexMap, IndexSet};
+use serde_json::Value;
+use std::{borrow::Cow, env, path::PathBuf};
+
+/// The variables visible to placeholders in one configuration file.
+#[derive(Debug, Clone)]
+pub(crate) struct Variables {
+    /// Module arguments given by the including graph.
+    pub args: IndexMap<Ident, Value>,
+    /// The namespace of the module. It is empty for the root graph.
+    pub module: Dir,
+    /// The directory containing the configuration file.
+    pub config_dir: PathBuf,
+}
+
+impl Default for Variables {
+    fn default() -> Self {
+        Self {
+            args: IndexMap::new(),
+            module: Dir::new(vec![]),
+            config_dir: PathBuf::new(),
+        }
+    }
+}
+
+/// Substitute `${name}` and `${name:-default}` placeholders in string values.
+///
+/// The name is looked up in module arguments, built-in variables
+/// `config_dir` and `module`, and environment variables in order. The
+/// default value is used if none of them is defined.
+///
+/// A string that consists of exactly one placeholder is replaced by the
+/// variable value, keeping its JSON type. Otherwise, the value is
+/// formatted into the string. The sequence `$${` produces a literal `${`.
+#[derive(Debug)]
+pub(crate) struct Substitution<'a> {
+    vars: &'a Variables,
+    used: IndexSet<Ident>,
+}
+
+impl<'a> Substitution<'a> {
+    pub fn new(vars: &'a Variables) -> Self {
+        Self {
+            vars,
+            used: IndexSet::new(),
+        }
+    }
+
+    /// Substitute placeholders in all strings within the value recursively.
+    pub fn apply(&mut self, value: &mut Value) -> Result<()> {
+        match value {
+            Value::String(text) => {
+                if let Some(new_value) = self.substitute(text)? {
+                    *value = new_value;
+                }
+            }
+            Value::Array(array) => {
+                array.iter_mut().try_for_each(|value| self.apply(value))?;
+            }
+            Value::Object(object) => {
+                object
+                    .values_mut()
+                    .try_for_each(|value| self.apply(value))?;
+            }
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Iterate over module arguments that no placeholders refer to.
+    pub fn unused_args(&self) -> impl Iterator<Item = &Ident> {
+        self.vars
+            .args
+            .keys()
+            .filter(|name| !self.used.contains(*name))
+    }
+
+    /// Substitute placeholders in a string. It returns `None` if the
+    /// string has no placeholders.
+    fn substitute(&mut self, text: &str) -> Result<Option<Value>> {
+        if !text.contains('$') {
+            return Ok(None);
+        }
+
+        let mut output = String::new();
+        let mut rest = text;
+
+        while let Some(pos) = rest.find('$') {
+            output.push_str(&rest[..pos]);
+            let suffix = &rest[pos..];
+
+            if let Some(suffix) = suffix.strip_prefix("$${") {
+                output.push_str("${");
+                rest = suffix;
+            } else if let Some(suffix) = suffix.strip_prefix("${") {
+                let Some(end) = suffix.find('}') else {
+                    bail!("unterminated placeholder in '{}'", text);
+                };
+                let value = self
+                    .lookup(&suffix[..end])
+                    .with_context(|| format!("unable to substitute '{}'", text))?;
+
+                // keep the JSON type if the whole string is one placeholder
+                if rest.len() == text.len() && pos == 0 && end + 3 == text.len() {
+                    return Ok(Some(value.into_owned()));
+                }
+
+                match &*value {
+                    Value::String(value) => output.push_str(value),
+                    value => output.push_str(&value.to_string()),
+                }
+                rest = &suffix[end + 1..];
+            } else {
+                output.push('$');
+                rest = &suffix[1..];
+            }
+        }
+        output.push_str(rest);
+
+        Ok(Some(Value::String(output)))
+    }
+
+    fn lookup(&mut self, placeholder: &str) -> Result<Cow<'a, Value>> {
+        let (name, default) = match placeholder.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (placeholder, None),
+        };
+
+        // module arguments
+        let args = &self.vars.args;
+        if let Some((ident, value)) = Ident::new(name).and_then(|ident| args.get_key_value(&ident))
+        {
+            self.used.insert(ident.clone());
+            return Ok(Cow::Borrowed(value));
+        }
+
+        // built-in variables
+        match name {
+            "config_dir" => {
+                let config_dir = &self.vars.config_dir;
+                let Some(dir) = config_dir.to_str() else {
+                    bail!("the path '{}' is not Unicode", config_dir.display());
+                };
+                return Ok(Cow::Owned(dir.into()));
+            }
+            "module" => return Ok(Cow::Owned(self.vars.module.to_string().into())),
+            _ => {}
+        }
+
+        // environment variables
+        match env::var(name) {
+            Ok(value) => return Ok(Cow::Owned(value.into())),
+            Err(env::VarError::NotPresent) => {}
+            Err(env::VarError::NotUnicode(_)) => {
+                bail!("the environment variable '{}' is not Unicode", name)
+            }
+        }
+
+        match default {
+            Some(default) => Ok(Cow::Owned(default.into())),
+            // a name in a module file is most likely a missing argument
+            None if !self.vars.module.is_root() => bail!(
+                "module argument '{}' is not provided, \
+                 nor is it a built-in variable or environment variable",
+                name
+            ),
+            None => bail!(
+                "'{}' is not a built-in variable or environment variable",
+                name
+            ),
+        }
+    }
+}
+
+/// Escape `${` to `$${` in exchange configurations, launch specs and
+/// module arguments of a graph configuration document, so that the strings
+/// are kept literally after substitution.
+pub(crate) fn escape_placeholders(doc: &mut Value) {
+    fn escape(value: &mut Value) {
+        match value {
+            Value::String(text) => {
+                if text.contains("${") {
+                    *text = text.replace("${", "$${");
+                }
+            }
+            Value::Array(array) => array.iter_mut().for_each(escape),
+            Value::Object(object) => object.values_mut().for_each(escape),
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+    }
+
+    for section in ["exchanges", "launch"] {
+        if let Some(value) = doc.get_mut(section) {
+            escape(value);
+        }
+    }
+    if let Some(Value::Object(modules)) = doc.get_mut("modules") {
+        modules
+            .values_mut()
+            .filter_map(|module| module.get_mut("args"))
+            .for_each(escape);
+    }
+}
@@ -0,0 +1,68 @@
+use easyflow_config::{GraphConfig, Lint, Severity, Span};
+use std::path::Path;
+
+const CONFIG_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/config-examples");
+
+#[test]
+fn check_test() {
+    let path = Path::new(CONFIG_DIR).join("check/typos.json5");
+    let diagnostics = GraphConfig::check(path);
+    assert!(diagnostics
+        .iter()
+        .all(|diag| diag.severity == Severity::Error));
+
+    let problems: Vec<_> = diagnostics
+        .iter()
+        .map(|diag| {
+            let file_name = diag.file.as_ref().unwrap().file_name().unwrap();
+            let line = diag.span.as_ref().map(|span| span.line);
+            (
+                file_name.to_str().unwrap(),
+                line,
+                diag.suggestion.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        problems,
+        [
+            ("typos.json5", Some(13), Some("merger")),
+            ("typos.json5", Some(15), Some("OUTPUT")),
+            ("camera.json5", Some(13), Some("camera")),
+            ("typos.json5", Some(24), None),
+            ("typos.json5", Some(18), Some("camera/VIDEO")),
+        ]
+    );
+
+    assert!(diagnostics[3].message.contains("unable to load module"));
+}
+
+#[test]
+fn check_valid_test() {
+    let path = Path::new(CONFIG_DIR).join("template.json5");
+
+    // the camera outputs are not consumed, which is only warned
+    let diagnostics = GraphConfig::check(path);
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics
+        .iter()
+        .all(|diag| diag.severity == Severity::Warning && diag.lint == Some(Lint::NoConsumer)));
+}
+
+#[test]
+fn locate_test() {
+    let text = r#"{
+    "exchanges": {
+        "CAM.RAW": { "type": "null" },
+        "CAM": { "type": "null" },
+    },
+}"#;
+    let span = Span::locate(text, "exchanges", "CAM").unwrap();
+    assert_eq!((span.line, span.column), (4, 10));
+    assert_eq!(&text[span.range], "CAM");
+
+    // unquoted keys are matched as whole words
+    let text = "[exchanges.CAM_RAW]\ntype = \"null\"\n\n[exchanges.CAM]\ntype = \"null\"\n";
+    let span = Span::locate(text, "exchanges", "CAM").unwrap();
+    assert_eq!((span.line, span.column), (4, 12));
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use easyflow_config::{GraphConfig, Key};
+use easyflow_link::Config as Exchange;
+
+const CONFIG_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/config-examples/formats.toml");
+
+#[test]
+fn mixed_formats_test() -> Result<()> {
+    let config = GraphConfig::open(CONFIG_FILE)?;
+
+    // the exchange is imported from a TOML file
+    let output: Key = "OUTPUT".parse().unwrap();
+    let Exchange::Import(import) = &config.exchanges[&output] else {
+        panic!("expect an import exchange");
+    };
+    assert!(matches!(*import.file, Exchange::Null(_)));
+
+    // modules are written in YAML and JSON5
+    let graph = config.flatten()?;
+    let video: Key = "camera/VIDEO".parse().unwrap();
+    let Exchange::Zenoh(zenoh) = &graph.exchanges[&video] else {
+        panic!("expect a zenoh exchange");
+    };
+    assert_eq!(zenoh.key, "camera0/video");
+
+    let points: Key = "lidar/POINTS".parse().unwrap();
+    assert!(matches!(graph.exchanges[&points], Exchange::Null(_)));
+
+    let camera: Key = "camera/camera".parse().unwrap();
+    assert!(graph.processors.contains(&camera));
+
+    Ok(())
+}
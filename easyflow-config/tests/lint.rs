@@ -0,0 +1,67 @@
+use anyhow::Result;
+use easyflow_config::{GraphConfig, Lint, LintLevel, Severity};
+
+const CONFIG_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/config-examples/lint.json5");
+
+#[test]
+fn lint_test() {
+    let diagnostics = GraphConfig::check(CONFIG_FILE);
+    assert!(diagnostics
+        .iter()
+        .all(|diag| diag.severity == Severity::Warning));
+
+    let problems: Vec<_> = diagnostics
+        .iter()
+        .map(|diag| {
+            let file_name = diag.file.as_ref().unwrap().file_name().unwrap();
+            let line = diag.span.as_ref().map(|span| span.line);
+            (diag.lint.unwrap(), file_name.to_str().unwrap(), line)
+        })
+        .collect();
+
+    // OUTPUT has no consumer, but the lint is allowed in the file
+    assert_eq!(
+        problems,
+        [
+            (Lint::NoProducer, "lint.json5", Some(8)),
+            (Lint::DanglingExchange, "lint.json5", Some(14)),
+            (Lint::IsolatedProcessor, "lint.json5", Some(5)),
+            (Lint::UnusedModuleProcessor, "sensor.json5", Some(5)),
+        ]
+    );
+}
+
+#[test]
+fn lint_deny_test() -> Result<()> {
+    // warnings do not stop loading
+    let config = GraphConfig::open(CONFIG_FILE)?;
+    let graph = config.clone().flatten()?;
+
+    let levels = [(Lint::DanglingExchange, LintLevel::Allow)]
+        .into_iter()
+        .collect();
+    let lints: Vec<_> = graph
+        .lint(&levels)
+        .into_iter()
+        .map(|diag| diag.lint)
+        .collect();
+    assert_eq!(
+        lints,
+        [
+            Some(Lint::NoProducer),
+            Some(Lint::NoConsumer),
+            Some(Lint::IsolatedProcessor),
+            Some(Lint::UnusedModuleProcessor),
+        ]
+    );
+
+    // a denied lint fails loading
+    let mut config = config;
+    config
+        .lints
+        .insert(Lint::IsolatedProcessor, LintLevel::Deny);
+    let err = config.flatten().unwrap_err();
+    assert!(err.to_string().contains("'idle'"));
+
+    Ok(())
+}
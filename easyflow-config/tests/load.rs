@@ -1,4 +1,5 @@
 use anyhow::Result;
+use easyflow_config::GraphConfig;
 use std::{fs, path::Path};
 
 #[test]
@@ -9,15 +10,15 @@ fn parse_graph_test() -> Result<()> {
         let entry = entry?;
         let path = entry.path();
 
-        // filter out files which extensions are not ".json5"
-        let Some(ext) = path.extension() else {
+        // filter out files which are not configuration files
+        let Some(ext) = path.extension().and_then(|ext| ext.to_str()) else {
             continue;
         };
-        if ext != "json5" {
+        if !matches!(ext, "json5" | "json" | "toml" | "yaml" | "yml") {
             continue;
         }
 
-        // let _graph = Dataflow::open(&path)?;
+        let _graph = GraphConfig::open(&path)?.flatten()?;
     }
 
     Ok(())
@@ -0,0 +1,80 @@
+use anyhow::Result;
+use easyflow_config::{migrate, migrate_file, GraphConfig, Key, CURRENT_VERSION};
+use easyflow_link::Config as Exchange;
+use serde_json::json;
+use std::{fs, path::Path};
+
+const CONFIG_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/config-examples/migrate");
+
+#[test]
+fn open_legacy_test() -> Result<()> {
+    let path = Path::new(CONFIG_DIR).join("legacy.json5");
+    let config = GraphConfig::open(&path)?;
+    let record: Key = "RECORD".parse().unwrap();
+    let Exchange::File(file) = &config.exchanges[&record] else {
+        panic!("expect a file exchange");
+    };
+    // `${` in version 0.1.0 is a placeholder
+    assert_eq!(file.dir.get(), &Path::new(CONFIG_DIR).join("record"));
+
+    Ok(())
+}
+
+#[test]
+fn migrate_test() -> Result<()> {
+    let mut doc = json!({
+        "version": "0.1.0",
+        "processors": [],
+        "exchanges": {
+            "A": { "type": "file", "dir": "${x}_$${y}" },
+        },
+        "connections": {},
+        "modules": {
+            "m": { "path": "m.json5", "args": { "name": "${z}" } },
+        },
+    });
+    let expect = json!({
+        "version": CURRENT_VERSION,
+        "processors": [],
+        "exchanges": {
+            "A": { "type": "file", "dir": "${x}_$${y}" },
+        },
+        "connections": {},
+        "modules": {
+            "m": { "path": "m.json5", "args": { "name": "${z}" } },
+        },
+    });
+    assert!(migrate(&mut doc)?);
+    assert_eq!(doc, expect);
+
+    // the current version is left untouched
+    assert!(!migrate(&mut doc)?);
+
+    // newer and unknown versions are rejected
+    let mut doc = json!({ "version": "0.3.0" });
+    assert!(migrate(&mut doc).is_err());
+    let mut doc = json!({ "version": "0.0.1" });
+    assert!(migrate(&mut doc).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn migrate_file_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("legacy.json5");
+    fs::copy(Path::new(CONFIG_DIR).join("legacy.json5"), &path)?;
+
+    assert!(migrate_file(&path)?);
+    assert!(!migrate_file(&path)?);
+
+    // only the version is replaced, keeping comments
+    let orig = fs::read_to_string(Path::new(CONFIG_DIR).join("legacy.json5"))?;
+    let expect = orig.replace(r#""version": "0.1.0""#, r#""version": "0.2.0""#);
+    assert_eq!(fs::read_to_string(&path)?, expect);
+
+    let config = GraphConfig::open(&path)?;
+    assert_eq!(config.processors.len(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,225 @@
+use anyhow::Result;
+use easyflow_config::{Dir, GraphConfig, Ident, IncludeCycle, IncludeStack, Key};
+use easyflow_link::Config as Exchange;
+use std::path::Path;
+
+const CONFIG_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/config-examples");
+
+#[test]
+fn cyclic_include_test() -> Result<()> {
+    let path = Path::new(CONFIG_DIR).join("cyclic/first.json5");
+    let config = GraphConfig::open(path)?;
+
+    let err = config.flatten().unwrap_err();
+    let cycle: &IncludeCycle = err.downcast_ref().expect("expect an include cycle error");
+    let chain: Vec<_> = cycle
+        .chain
+        .iter()
+        .map(|include| {
+            let file_name = include.path.file_name().unwrap().to_str().unwrap();
+            (include.ident.as_str(), file_name)
+        })
+        .collect();
+    assert_eq!(
+        chain,
+        [
+            ("second", "second.json5"),
+            ("first", "first.json5"),
+            ("second", "second.json5")
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn missing_module_test() {
+    let path = Path::new(CONFIG_DIR).join("missing.json5");
+    let mut stack = IncludeStack::new();
+
+    let err = stack.push("missing".parse().unwrap(), &path).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&format!("unable to load module '{}'", path.display())));
+    assert!(stack.includes().is_empty());
+}
+
+#[test]
+fn diamond_include_test() -> Result<()> {
+    let path = Path::new(CONFIG_DIR).join("diamond.json5");
+    let graph = GraphConfig::open(path)?.flatten()?;
+
+    let dirs: Vec<_> = graph.bindings.keys().map(Dir::to_string).collect();
+    assert_eq!(dirs, ["left", "right", "left/shared", "right/shared"]);
+    assert_eq!(graph.bindings[2], graph.bindings[3]);
+
+    let processors: Vec<_> = graph.processors.iter().map(Key::to_string).collect();
+    assert_eq!(
+        processors,
+        ["left", "left/shared/shared", "right", "right/shared/shared"]
+    );
+
+    let key: Key = "right/shared/null-shared".parse().unwrap();
+    let sources: Vec<_> = graph.connections[&key]
+        .source_iter()
+        .map(Key::to_string)
+        .collect();
+    assert_eq!(sources, ["right/shared/shared"]);
+
+    let exchanges: Vec<_> = graph.exchanges.keys().map(|key| key.to_string()).collect();
+    assert_eq!(
+        exchanges,
+        [
+            "left/null-left",
+            "left/shared/null-shared",
+            "right/null-right",
+            "right/shared/null-shared"
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn flat_processor_collision_test() -> Result<()> {
+    let path = Path::new(CONFIG_DIR).join("collision/main.json5");
+    let config = GraphConfig::open(path)?;
+
+    let err = config.flatten().unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("processor 'loader' is declared by both modules 'first' and 'second'"));
+
+    Ok(())
+}
+
+#[test]
+fn module_key_collision_test() -> Result<()> {
+    let path = Path::new(CONFIG_DIR).join("collision/key.json5");
+
+    let err = GraphConfig::open(&path)?.flatten().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "exchange 'cam/VIDEO' is declared by both the root graph and module 'cam'"
+    );
+
+    let messages: Vec<_> = GraphConfig::check(&path)
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "exchange 'cam/VIDEO' is declared by both the root graph and module 'cam'",
+            "processor 'cam/camera' is declared by both the root graph and module 'cam'",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn module_exchange_connection_test() -> Result<()> {
+    let path = Path::new(CONFIG_DIR).join("module-connection.json5");
+    let config = GraphConfig::open(path)?;
+
+    // the parent connection is merged with the module connection
+    let graph = config.clone().flatten()?;
+    let key: Key = "camera/VIDEO".parse().unwrap();
+    let conn = &graph.connections[&key];
+    let sinks: Vec<_> = conn.sink_iter().map(Key::to_string).collect();
+    let sources: Vec<_> = conn.source_iter().map(Key::to_string).collect();
+    assert_eq!(sinks, ["camera"]);
+    assert_eq!(sources, ["merger"]);
+
+    // connecting to an exchange that the module does not declare is an error
+    let mut config = config;
+    let conn = config.connections.remove(&key).unwrap();
+    config
+        .connections
+        .insert("camera/AUDIO".parse().unwrap(), conn);
+    let err = config.flatten().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "'camera/AUDIO' is not an exchange declared in modules"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn module_arguments_test() -> Result<()> {
+    let path = Path::new(CONFIG_DIR).join("template.json5");
+    let config = GraphConfig::open(path)?;
+
+    // arguments are substituted into each instance of the module
+    let graph = config.clone().flatten()?;
+    let exchanges: Vec<_> = graph
+        .exchanges
+        .iter()
+        .map(|(key, exchange)| {
+            let Exchange::File(file) = exchange else {
+                panic!("expect a file exchange");
+            };
+            let dir = file.dir.file_name().unwrap().to_str().unwrap().to_string();
+            (key.to_string(), dir, file.auto_clean)
+        })
+        .collect();
+    assert_eq!(
+        exchanges,
+        [
+            ("cam0/VIDEO".to_string(), "camera0_output".to_string(), true),
+            (
+                "cam1/VIDEO".to_string(),
+                "camera1_output".to_string(),
+                false
+            ),
+        ]
+    );
+
+    let cam0: Ident = "cam0".parse().unwrap();
+    let index: Ident = "index".parse().unwrap();
+
+    // a placeholder without the argument is an error
+    let mut missing = config.clone();
+    missing.modules.as_mut().unwrap()[&cam0].args.remove(&index);
+    let err = missing.flatten().unwrap_err();
+    assert!(format!("{:#}", err).contains("module argument 'index' is not provided"));
+
+    // an argument without placeholders is an error
+    let mut unused = config;
+    unused.modules.as_mut().unwrap()[&cam0]
+        .args
+        .insert("rate".parse().unwrap(), 30.into());
+    let err = unused.flatten().unwrap_err();
+    assert!(format!("{:#}", err).contains("unused module arguments: rate"));
+
+    Ok(())
+}
+
+#[test]
+fn package_module_test() -> Result<()> {
+    let config_dir = Path::new(CONFIG_DIR).canonicalize()?;
+    let path = config_dir.join("package/main.json5");
+
+    // the package is not found without the search path
+    let err = GraphConfig::open(&path)?.flatten().unwrap_err();
+    assert!(format!("{:#}", err).contains("'@sensors/camera.json5' is not found"));
+
+    let mut stack = IncludeStack::with_search_path([config_dir.join("package/lib")]);
+    let graph = GraphConfig::open(&path)?.flatten_in::<_, &Path>(&mut stack, [])?;
+
+    let front: Dir = "front".parse::<Key>().unwrap().into_dir();
+    assert_eq!(
+        graph.bindings[&front],
+        config_dir.join("package/lib/sensors/camera.json5")
+    );
+
+    let video: Key = "front/VIDEO".parse().unwrap();
+    let Exchange::Zenoh(zenoh) = &graph.exchanges[&video] else {
+        panic!("expect a zenoh exchange");
+    };
+    assert_eq!(zenoh.key, "front/video");
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use anyhow::Result;
+use easyflow_config::graph_schema;
+use serde_json::Value;
+
+#[test]
+fn graph_schema_test() -> Result<()> {
+    let schema = serde_json::to_value(graph_schema())?;
+
+    let required = schema["required"].as_array().unwrap();
+    for field in ["version", "processors", "exchanges", "connections"] {
+        assert!(required.contains(&Value::from(field)));
+    }
+
+    // connections use "<" and ">" for producers and consumers
+    let connection = &schema["definitions"]["Connection"]["properties"];
+    assert!(connection.get("<").is_some());
+    assert!(connection.get(">").is_some());
+
+    // each exchange type is a tagged variant
+    let types: Vec<_> = schema["definitions"]["Exchange"]["oneOf"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|variant| variant["properties"]["type"]["enum"][0].as_str().unwrap())
+        .collect();
+    // zenoh is enabled by default features of easyflow-link
+    for ty in ["file", "zenoh", "null", "import"] {
+        assert!(types.contains(&ty));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use easyflow_config::{GraphConfig, Key};
+use easyflow_link::Config as Exchange;
+use std::{env, path::Path, process::Command};
+
+const CONFIG_FILE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/config-examples/variables.json5"
+);
+const TOPIC_VAR: &str = "EASYFLOW_EXAMPLE_LIDAR_TOPIC";
+const OUTPUT_VAR: &str = "EASYFLOW_EXAMPLE_OUTPUT";
+const CHILD_VAR: &str = "EASYFLOW_TEST_CHILD";
+
+fn lidar_key(config: GraphConfig) -> Result<String> {
+    let graph = config.flatten()?;
+    let key: Key = "lidar/POINTS".parse().unwrap();
+    let Exchange::Zenoh(zenoh) = &graph.exchanges[&key] else {
+        panic!("expect a zenoh exchange");
+    };
+    Ok(zenoh.key.clone())
+}
+
+/// Run a test again in a child process with a controlled environment
+/// and working directory, since both are shared by tests in this
+/// process. Returns `false` when already running in the child.
+fn run_in_child(test: &str, dir: &Path, vars: &[(&str, &str)]) -> Result<bool> {
+    if env::var_os(CHILD_VAR).is_some() {
+        return Ok(false);
+    }
+    let output = Command::new(env::current_exe()?)
+        .args(["--exact", test])
+        .current_dir(dir)
+        .env(CHILD_VAR, "1")
+        .env_remove(TOPIC_VAR)
+        .env_remove(OUTPUT_VAR)
+        .envs(vars.iter().copied())
+        .output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("1 passed"), "{}", stdout);
+    Ok(true)
+}
+
+#[test]
+fn variable_substitution_test() -> Result<()> {
+    if run_in_child("variable_substitution_test", Path::new("."), &[])? {
+        return Ok(());
+    }
+
+    // built-in variables and default values
+    let config = GraphConfig::open(CONFIG_FILE)?;
+    let output: Key = "OUTPUT".parse().unwrap();
+    let Exchange::File(file) = &config.exchanges[&output] else {
+        panic!("expect a file exchange");
+    };
+    let config_dir = Path::new(CONFIG_FILE).parent().unwrap();
+    assert_eq!(file.dir.get(), &config_dir.join("output"));
+    assert_eq!(lidar_key(config)?, "lidar/scan");
+
+    Ok(())
+}
+
+#[test]
+fn environment_variable_test() -> Result<()> {
+    if run_in_child(
+        "environment_variable_test",
+        Path::new("."),
+        &[(TOPIC_VAR, "points")],
+    )? {
+        return Ok(());
+    }
+
+    // environment variables override default values
+    let config = GraphConfig::open(CONFIG_FILE)?;
+    assert_eq!(lidar_key(config)?, "lidar/points");
+
+    Ok(())
+}
+
+#[test]
+fn relative_config_dir_test() -> Result<()> {
+    let config_dir = Path::new(CONFIG_FILE).parent().unwrap();
+    if run_in_child("relative_config_dir_test", config_dir, &[])? {
+        return Ok(());
+    }
+
+    // the config directory of a bare file name is the working directory
+    let config = GraphConfig::open("variables.json5")?;
+    let output: Key = "OUTPUT".parse().unwrap();
+    let Exchange::File(file) = &config.exchanges[&output] else {
+        panic!("expect a file exchange");
+    };
+    assert_eq!(file.dir.get(), &env::current_dir()?.join("output"));
+
+    Ok(())
+}
@@ -1,6 +1,9 @@
 #![cfg(feature = "amqp")]
 
-use crate::common::*;
+use crate::{
+    common::*,
+    message::{self, Message, MessageConfig, Meta, Stamper},
+};
 use amq_protocol_types::{LongString, ShortString};
 use anyhow::ensure;
 use lapin::{
@@ -13,7 +16,7 @@ use std::{borrow::Cow, collections::BTreeMap, env, env::VarError, sync::Arc, tim
 
 static ENV_ADDRESS: &str = "AMQP_ADDRESS";
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
     pub address: String,
     pub exchange: String,
@@ -23,9 +26,12 @@ pub struct Config {
     pub reliable: bool,
     #[serde(default = "default_force")]
     pub force: bool,
+    /// The message format shared by all transports.
+    #[serde(flatten)]
+    pub message: MessageConfig,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum Overflow {
     DropHead,
     RejectPublish,
@@ -39,11 +45,14 @@ impl Config {
             message_ttl_millis,
             reliable,
             force,
+            ref message,
             ..
         } = *self;
 
+        let address = address_from_env(address);
+
         let channel = {
-            let conn = Connection::connect(address, Default::default()).await?;
+            let conn = Connection::connect(&address, Default::default()).await?;
             let channel = conn.create_channel().await?;
             channel.basic_qos(1, Default::default()).await?;
             if reliable {
@@ -60,6 +69,7 @@ impl Config {
             channel,
             exchange: Arc::new(exchange.to_owned()),
             reliable,
+            envelope: message.envelope.then(Stamper::default),
         })
     }
 
@@ -84,25 +94,10 @@ impl Config {
             message_ttl_millis,
             reliable,
             force,
+            ..
         } = *self;
 
-        let address: Cow<'_, str> = match env::var(ENV_ADDRESS) {
-            Ok(addr) => {
-                info!(
-                    r#"The environment variable "{}" overrides the AMQP address"#,
-                    ENV_ADDRESS
-                );
-                addr.into()
-            }
-            Err(VarError::NotPresent) => address.into(),
-            Err(VarError::NotUnicode(_)) => {
-                warn!(
-                    r#"The environment variable "{}" is set but not Unicode"#,
-                    ENV_ADDRESS
-                );
-                address.into()
-            }
-        };
+        let address = address_from_env(address);
 
         let overflow = if reliable {
             Overflow::RejectPublish
@@ -175,6 +170,7 @@ impl Config {
             queue,
             consumer,
             channel,
+            envelope: self.message.envelope,
         })
     }
 }
@@ -185,6 +181,7 @@ pub struct Sender {
     channel: Channel,
     exchange: Arc<String>,
     reliable: bool,
+    envelope: Option<Stamper>,
 }
 
 impl Sender {
@@ -202,27 +199,50 @@ impl Sender {
         K: AsRef<str>,
         V: AsRef<str>,
     {
+        let headers: BTreeMap<String, String> = headers
+            .into_iter()
+            .map(|(key, value)| (key.as_ref().to_owned(), value.as_ref().to_owned()))
+            .collect();
+        self.send_with_headers(payload, headers).await
+    }
+
+    /// Send a message with headers. In the envelope mode, the
+    /// sequence number, the producer and the send time are carried in
+    /// the message id, the app id and the timestamp properties. The
+    /// timestamp property counts seconds, and the precise time is
+    /// kept in the `easyflow-timestamp` header.
+    pub async fn send_with_headers(
+        &self,
+        payload: &[u8],
+        headers: BTreeMap<String, String>,
+    ) -> Result<()> {
         let Self {
             ref channel,
             ref exchange,
             message_ttl_millis,
             reliable,
-            ..
+            ref envelope,
         } = *self;
 
-        let headers: FieldTable = {
-            let headers: BTreeMap<ShortString, AMQPValue> = headers
-                .into_iter()
-                .map(|(key, value)| {
-                    let key = key.as_ref();
-                    let value = value.as_ref();
-                    (key.into(), LongString::from(value).into())
-                })
-                .collect();
-            headers.into()
+        let properties = match envelope {
+            Some(stamper) => {
+                let meta = stamper.stamp(headers)?;
+                let mut headers = header_table(&meta.headers);
+                headers.insert(
+                    message::TIMESTAMP_KEY.into(),
+                    LongString::from(message::format_timestamp(&meta.timestamp)).into(),
+                );
+                let properties = BasicProperties::default()
+                    .with_headers(headers.into())
+                    .with_message_id(meta.seq.to_string().into())
+                    .with_timestamp(meta.timestamp.timestamp() as u64);
+                match meta.producer {
+                    Some(producer) => properties.with_app_id(producer.into()),
+                    None => properties,
+                }
+            }
+            None => BasicProperties::default().with_headers(header_table(&headers).into()),
         };
-
-        let properties = BasicProperties::default().with_headers(headers);
         let properties = match message_ttl_millis {
             Some(ttl) => properties.with_expiration(ttl.to_string().into()),
             None => properties,
@@ -274,6 +294,12 @@ impl Sender {
         Ok(())
     }
 
+    pub fn set_producer(&mut self, producer: impl Into<String>) {
+        if let Some(stamper) = &mut self.envelope {
+            stamper.set_producer(producer);
+        }
+    }
+
     pub fn into_sink(self) -> impl Sink<Vec<u8>, Error = Error> {
         sink::unfold(self, |sender, payload: Vec<u8>| async move {
             sender.send(&payload).await.map(|_| sender)
@@ -286,12 +312,21 @@ pub struct Receiver {
     queue: Queue,
     consumer: Consumer,
     channel: Channel,
+    envelope: bool,
 }
 
 impl Receiver {
     pub async fn recv(&mut self) -> Result<Option<Vec<u8>>> {
+        let message = self.recv_message().await?;
+        Ok(message.map(|message| message.payload))
+    }
+
+    pub async fn recv_message(&mut self) -> Result<Option<Message>> {
         let Self {
-            consumer, channel, ..
+            consumer,
+            channel,
+            envelope,
+            ..
         } = self;
 
         let delivery = match consumer.next().await.transpose()? {
@@ -303,7 +338,16 @@ impl Receiver {
             .basic_ack(delivery.delivery_tag, Default::default())
             .await?;
 
-        Ok(Some(delivery.data))
+        let meta = if *envelope {
+            Some(meta_from_properties(&delivery.properties)?)
+        } else {
+            None
+        };
+
+        Ok(Some(Message {
+            payload: delivery.data,
+            meta,
+        }))
     }
 
     pub fn queue_name(&self) -> &str {
@@ -318,6 +362,65 @@ impl Receiver {
     }
 }
 
+fn header_table(headers: &BTreeMap<String, String>) -> BTreeMap<ShortString, AMQPValue> {
+    headers
+        .iter()
+        .map(|(key, value)| (key.as_str().into(), LongString::from(value.as_str()).into()))
+        .collect()
+}
+
+/// Collect the metadata written by [Sender::send_with_headers].
+/// Header values other than strings are ignored.
+fn meta_from_properties(properties: &BasicProperties) -> Result<Meta> {
+    let mut fields: Vec<(String, String)> = vec![];
+
+    if let Some(headers) = properties.headers() {
+        fields.extend(headers.inner().iter().filter_map(|(key, value)| {
+            let value = match value {
+                AMQPValue::LongString(value) => {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                }
+                AMQPValue::ShortString(value) => value.as_str().to_owned(),
+                _ => return None,
+            };
+            Some((key.as_str().to_owned(), value))
+        }));
+    }
+    if let Some(seq) = properties.message_id() {
+        fields.push((message::SEQ_KEY.to_owned(), seq.as_str().to_owned()));
+    }
+    if let Some(producer) = properties.app_id() {
+        fields.push((
+            message::PRODUCER_KEY.to_owned(),
+            producer.as_str().to_owned(),
+        ));
+    }
+
+    Meta::from_fields(fields)
+}
+
+/// Return the address in the `AMQP_ADDRESS` environment variable if
+/// it is set. Otherwise, return the configured address.
+fn address_from_env(address: &str) -> Cow<'_, str> {
+    match env::var(ENV_ADDRESS) {
+        Ok(addr) => {
+            info!(
+                r#"The environment variable "{}" overrides the AMQP address"#,
+                ENV_ADDRESS
+            );
+            addr.into()
+        }
+        Err(VarError::NotPresent) => address.into(),
+        Err(VarError::NotUnicode(_)) => {
+            warn!(
+                r#"The environment variable "{}" is set but not Unicode"#,
+                ENV_ADDRESS
+            );
+            address.into()
+        }
+    }
+}
+
 async fn declare_exchange(channel: &Channel, name: &str, force: bool) -> Result<()> {
     if force {
         channel.exchange_delete(name, Default::default()).await?;
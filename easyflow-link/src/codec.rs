@@ -0,0 +1,82 @@
+use crate::common::*;
+use serde::de::DeserializeOwned;
+use std::fmt::{self, Display};
+
+/// The encoding of messages sent by typed senders and received by
+/// typed receivers.
+///
+/// It is declared by the `codec` field of an exchange, so that the
+/// processors on both sides agree on the encoding. Exchanges without
+/// the field use bincode.
+///
+/// ```json5
+/// "OUTPUT": {
+///     "type": "zenoh",
+///     "key": "output",
+///     "codec": "json",
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Codec {
+    /// [bincode](https://github.com/bincode-org/bincode)
+    #[default]
+    Bincode,
+    /// JSON
+    Json,
+    /// [MessagePack](https://msgpack.org/) with named struct fields
+    #[serde(rename = "msgpack")]
+    MessagePack,
+    /// [CBOR](https://cbor.io/)
+    Cbor,
+}
+
+impl Codec {
+    /// The name used in the `codec` field.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bincode => "bincode",
+            Self::Json => "json",
+            Self::MessagePack => "msgpack",
+            Self::Cbor => "cbor",
+        }
+    }
+
+    /// Encode a value to bytes.
+    pub fn encode<T>(&self, value: &T) -> Result<Vec<u8>>
+    where
+        T: Serialize + ?Sized,
+    {
+        let bytes = match self {
+            Self::Bincode => bincode::serialize(value)?,
+            Self::Json => serde_json::to_vec(value)?,
+            Self::MessagePack => rmp_serde::to_vec_named(value)?,
+            Self::Cbor => {
+                let mut bytes = vec![];
+                ciborium::into_writer(value, &mut bytes)?;
+                bytes
+            }
+        };
+        Ok(bytes)
+    }
+
+    /// Decode a value from bytes.
+    pub fn decode<T>(&self, bytes: &[u8]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let value = match self {
+            Self::Bincode => bincode::deserialize(bytes)?,
+            Self::Json => serde_json::from_slice(bytes)?,
+            Self::MessagePack => rmp_serde::from_slice(bytes)?,
+            Self::Cbor => ciborium::from_reader(bytes)?,
+        };
+        Ok(value)
+    }
+}
+
+impl Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.name().fmt(f)
+    }
+}
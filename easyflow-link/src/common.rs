@@ -1,3 +1,4 @@
+pub use crate::codec::Codec;
 pub use anyhow::{bail, Error, Result};
 pub use chrono::{DateTime, Local, SecondsFormat};
 pub use futures::{
@@ -6,6 +7,7 @@ pub use futures::{
     stream::{self, Stream, StreamExt as _, TryStreamExt as _},
     AsyncReadExt as _, AsyncWriteExt as _,
 };
+pub use schemars::JsonSchema;
 pub use serde::{Deserialize, Serialize};
 pub use std::{
     borrow::{Borrow, Cow},
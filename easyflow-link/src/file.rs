@@ -1,16 +1,24 @@
-use crate::common::*;
+use crate::{
+    common::*,
+    message::{self, Message, MessageConfig, Stamper},
+};
 use async_std::{
     fs::{self, File},
     io::BufWriter,
     task::sleep,
 };
 use serde_loader::AbsPathBuf;
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
+    #[schemars(with = "PathBuf")]
     pub dir: AbsPathBuf,
     #[serde(default)]
     pub auto_clean: bool,
+    /// The message format shared by all transports.
+    #[serde(flatten)]
+    pub message: MessageConfig,
 }
 
 impl Config {
@@ -18,6 +26,8 @@ impl Config {
         let Self {
             ref dir,
             auto_clean,
+            ref message,
+            ..
         } = *self;
 
         if auto_clean {
@@ -31,7 +41,10 @@ impl Config {
 
         fs::create_dir_all(dir.get()).await?;
 
-        Ok(Sender { dir: dir.clone() })
+        Ok(Sender {
+            dir: dir.clone(),
+            envelope: message.envelope.then(Stamper::default),
+        })
     }
 
     pub async fn build_receiver(&self) -> Result<Receiver> {
@@ -75,25 +88,45 @@ impl Config {
             files.into_iter().map(|(_, path)| path).collect()
         };
 
-        Ok(Receiver { index: 0, files })
+        Ok(Receiver {
+            index: 0,
+            files,
+            envelope: self.message.envelope,
+        })
     }
 }
 
 #[derive(Debug)]
 pub struct Sender {
     dir: AbsPathBuf,
+    envelope: Option<Stamper>,
 }
 
 impl Sender {
     pub async fn send(&self, payload: &[u8]) -> Result<()> {
+        self.send_with_headers(payload, BTreeMap::new()).await
+    }
+
+    pub async fn send_with_headers(
+        &self,
+        payload: &[u8],
+        headers: BTreeMap<String, String>,
+    ) -> Result<()> {
+        let data = message::frame(self.envelope.as_ref(), headers, payload)?;
         let file_name = Local::now().to_rfc3339_opts(SecondsFormat::Nanos, false);
         let path = self.dir.join(&file_name);
         let mut file = BufWriter::new(File::create(path).await?);
-        file.write_all(payload).await?;
+        file.write_all(&data).await?;
         file.flush().await?;
         Ok(())
     }
 
+    pub fn set_producer(&mut self, producer: impl Into<String>) {
+        if let Some(stamper) = &mut self.envelope {
+            stamper.set_producer(producer);
+        }
+    }
+
     pub fn into_sink(self) -> impl Sink<Vec<u8>, Error = Error> {
         sink::unfold(self, |sender, payload: Vec<u8>| async move {
             sender.send(&payload).await.map(|_| sender)
@@ -105,10 +138,16 @@ impl Sender {
 pub struct Receiver {
     index: usize,
     files: Vec<PathBuf>,
+    envelope: bool,
 }
 
 impl Receiver {
     pub async fn recv(&mut self) -> Result<Option<Vec<u8>>> {
+        let message = self.recv_message().await?;
+        Ok(message.map(|message| message.payload))
+    }
+
+    pub async fn recv_message(&mut self) -> Result<Option<Message>> {
         let path = match self.files.get(self.index) {
             Some(path) => path,
             None => return Ok(None),
@@ -116,10 +155,10 @@ impl Receiver {
         self.index += 1;
 
         let mut file = fs::File::open(path).await?;
-        let mut payload = Vec::<u8>::new();
-        file.read_to_end(&mut payload).await?;
+        let mut data = Vec::<u8>::new();
+        file.read_to_end(&mut data).await?;
 
-        Ok(Some(payload))
+        Ok(Some(message::unframe(self.envelope, data)?))
     }
 
     pub fn into_stream(self) -> impl Stream<Item = Result<Vec<u8>>> {
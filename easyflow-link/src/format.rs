@@ -0,0 +1,133 @@
+use crate::common::*;
+use serde::de::DeserializeOwned;
+use serde_loader::file::{FileDumper, FileLoader, FilePath};
+use std::{fs, path::Path};
+
+/// A wrapper type that opens and deserializes a configuration file
+/// in the format chosen by its file extension.
+pub type FormatPath<T> = FilePath<T, FormatDumper, FormatLoader>;
+
+/// Configuration file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    Json5,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    /// Choose the format by the file extension.
+    ///
+    /// - `.toml`: TOML
+    /// - `.yaml` or `.yml`: YAML
+    /// - `.json5`, `.json` or others: JSON5
+    pub fn from_path<P>(path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let ext = path.as_ref().extension().and_then(|ext| ext.to_str());
+        match ext {
+            Some("toml") => Self::Toml,
+            Some("yaml" | "yml") => Self::Yaml,
+            _ => Self::Json5,
+        }
+    }
+
+    /// Deserialize a value from the text.
+    pub fn parse<T>(self, text: &str) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let value = match self {
+            Self::Json5 => json5::from_str(text)?,
+            Self::Toml => toml::from_str(text)?,
+            Self::Yaml => serde_yaml::from_str(text)?,
+        };
+        Ok(value)
+    }
+
+    /// Serialize a value to text.
+    ///
+    /// JSON5 files are written in pretty-printed JSON, which is valid JSON5.
+    pub fn to_string<T>(self, value: &T) -> Result<String>
+    where
+        T: Serialize,
+    {
+        let text = match self {
+            Self::Json5 => serde_json::to_string_pretty(value)?,
+            Self::Toml => {
+                // TOML has no null. Drop null entries, which are absent optional fields.
+                let mut value = serde_json::to_value(value)?;
+                remove_nulls(&mut value);
+                toml::to_string_pretty(&value)?
+            }
+            Self::Yaml => serde_yaml::to_string(value)?,
+        };
+        Ok(text)
+    }
+
+    /// Read and deserialize a file in the format chosen by its extension.
+    pub fn load<T, P>(path: P) -> Result<T>
+    where
+        T: DeserializeOwned,
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        Self::from_path(path).parse(&text)
+    }
+}
+
+fn remove_nulls(value: &mut serde_json::Value) {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(object) => {
+            object.retain(|_, value| !value.is_null());
+            object.values_mut().for_each(remove_nulls);
+        }
+        Value::Array(array) => array.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FormatDumper {
+    _private: [u8; 0],
+}
+
+impl<T> FileDumper<T> for FormatDumper
+where
+    T: Serialize,
+{
+    type Error = anyhow::Error;
+
+    fn dump<P>(p: P, value: &T) -> Result<(), Self::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = p.as_ref();
+        let text = Format::from_path(path).to_string(value)?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FormatLoader {
+    _private: [u8; 0],
+}
+
+impl<T> FileLoader<T> for FormatLoader
+where
+    T: DeserializeOwned,
+{
+    type Error = anyhow::Error;
+
+    fn load<P>(p: P) -> Result<T, Self::Error>
+    where
+        P: AsRef<Path>,
+    {
+        Format::load(p)
+    }
+}
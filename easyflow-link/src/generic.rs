@@ -4,10 +4,17 @@ use crate::amqp;
 use crate::unix;
 #[cfg(feature = "zenoh")]
 use crate::zenoh;
-use crate::{common::*, file, import, null};
+use crate::{
+    common::*,
+    file, import,
+    message::{Message, MessageConfig},
+    null,
+};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[schemars(rename = "Exchange")]
 pub enum Config {
     File(file::Config),
     #[cfg(feature = "zenoh")]
@@ -21,6 +28,64 @@ pub enum Config {
 }
 
 impl Config {
+    /// Replace an `import` configuration by the configuration in the
+    /// imported file recursively. Other types are returned as is.
+    pub fn into_resolved(self) -> Self {
+        let mut config = self;
+        while let Self::Import(import) = config {
+            config = import.file.take();
+        }
+        config
+    }
+
+    /// The transport type written in the `type` field.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::File(_) => "file",
+            #[cfg(feature = "zenoh")]
+            Self::Zenoh(_) => "zenoh",
+            #[cfg(feature = "amqp")]
+            Self::Amqp(_) => "amqp",
+            #[cfg(all(unix, feature = "unix-sock"))]
+            Self::Unix(_) => "unix",
+            Self::Null(_) => "null",
+            Self::Import(_) => "import",
+        }
+    }
+
+    /// Describe where the messages go, such as the zenoh key or the
+    /// socket path. It returns `None` if there is no such address.
+    pub fn endpoint(&self) -> Option<String> {
+        let endpoint = match self {
+            Self::File(config) => config.dir.get().display().to_string(),
+            #[cfg(feature = "zenoh")]
+            Self::Zenoh(config) => config.key.clone(),
+            #[cfg(feature = "amqp")]
+            Self::Amqp(config) => format!("{} at {}", config.exchange, config.address),
+            #[cfg(all(unix, feature = "unix-sock"))]
+            Self::Unix(config) => config.path.display().to_string(),
+            Self::Null(_) | Self::Import(_) => return None,
+        };
+        Some(endpoint)
+    }
+
+    /// The message format declared along with the transport. For
+    /// `import` exchanges, it is the format of the imported
+    /// configuration.
+    pub fn message_config(&self) -> &MessageConfig {
+        match self {
+            Self::File(config) => &config.message,
+            #[cfg(feature = "zenoh")]
+            Self::Zenoh(config) => &config.message,
+            #[cfg(feature = "amqp")]
+            Self::Amqp(config) => &config.message,
+            #[cfg(all(unix, feature = "unix-sock"))]
+            Self::Unix(config) => &config.message,
+            Self::Null(config) => &config.message,
+            Self::Import(config) => config.file.message_config(),
+        }
+    }
+
     pub async fn build_sender(&self) -> Result<Sender> {
         let sender = match self {
             Self::File(config) => config.build_sender().await?.into(),
@@ -81,6 +146,41 @@ impl Sender {
         }
     }
 
+    /// Send a payload with user headers. Headers are rejected unless
+    /// the exchange is in the envelope mode or uses AMQP.
+    pub async fn send_with_headers(
+        &mut self,
+        payload: impl Into<Cow<'_, [u8]>>,
+        headers: BTreeMap<String, String>,
+    ) -> Result<()> {
+        let payload = payload.into();
+        match self {
+            Self::File(sender) => sender.send_with_headers(&payload, headers).await,
+            #[cfg(feature = "zenoh")]
+            Self::Zenoh(sender) => sender.send_with_headers(&payload, headers).await,
+            #[cfg(feature = "amqp")]
+            Self::Amqp(sender) => sender.send_with_headers(&payload, headers).await,
+            #[cfg(all(unix, feature = "unix-sock"))]
+            Self::Unix(sender) => sender.send_with_headers(&payload, headers).await,
+            Self::Null(sender) => sender.send_with_headers(&payload, headers),
+        }
+    }
+
+    /// Set the producer name attached to messages in the envelope
+    /// mode.
+    pub fn set_producer(&mut self, producer: impl Into<String>) {
+        match self {
+            Self::File(sender) => sender.set_producer(producer),
+            #[cfg(feature = "zenoh")]
+            Self::Zenoh(sender) => sender.set_producer(producer),
+            #[cfg(feature = "amqp")]
+            Self::Amqp(sender) => sender.set_producer(producer),
+            #[cfg(all(unix, feature = "unix-sock"))]
+            Self::Unix(sender) => sender.set_producer(producer),
+            Self::Null(sender) => sender.set_producer(producer),
+        }
+    }
+
     pub fn into_sink(self) -> impl Sink<Vec<u8>, Error = Error> {
         sink::unfold(self, |mut sender, payload| async move {
             sender.send(payload).await.map(|_| sender)
@@ -152,6 +252,21 @@ impl Receiver {
         }
     }
 
+    /// Receive a payload along with its metadata. The metadata is
+    /// `None` unless the exchange is in the envelope mode.
+    pub async fn recv_message(&mut self) -> Result<Option<Message>> {
+        match self {
+            Self::File(receiver) => receiver.recv_message().await,
+            #[cfg(feature = "zenoh")]
+            Self::Zenoh(receiver) => receiver.recv_message().await,
+            #[cfg(feature = "amqp")]
+            Self::Amqp(receiver) => receiver.recv_message().await,
+            #[cfg(all(unix, feature = "unix-sock"))]
+            Self::Unix(receiver) => receiver.recv_message().await,
+            Self::Null(receiver) => receiver.recv_message().await,
+        }
+    }
+
     pub fn into_stream(self) -> impl Stream<Item = Result<Vec<u8>>> {
         stream::try_unfold(self, |mut rx| async move {
             let item = rx.recv().await?;
@@ -159,6 +274,13 @@ impl Receiver {
         })
     }
 
+    pub fn into_message_stream(self) -> impl Stream<Item = Result<Message>> {
+        stream::try_unfold(self, |mut rx| async move {
+            let item = rx.recv_message().await?;
+            anyhow::Ok(item.map(|item| (item, rx)))
+        })
+    }
+
     #[cfg(feature = "protobuf")]
     pub fn into_protobuf_decoded<T: prost::Message + Default>(
         self,
@@ -1,10 +1,11 @@
-use crate::{common::*, generic};
+use crate::{common::*, format::FormatPath, generic};
 use futures::future::BoxFuture;
-use serde_loader::Json5Path;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
-    pub file: Json5Path<generic::Config>,
+    /// The exchange configuration file in JSON5, TOML or YAML format.
+    #[schemars(with = "PathBuf")]
+    pub file: FormatPath<generic::Config>,
 }
 
 impl Config {
@@ -1,10 +1,18 @@
 pub mod amqp;
+pub mod codec;
 mod common;
 pub mod file;
+pub mod format;
 pub mod generic;
 pub mod import;
+pub mod message;
 pub mod null;
+pub mod protobuf;
+pub mod typed;
 pub mod unix;
 pub mod zenoh;
 
+pub use codec::Codec;
 pub use generic::{Config, Receiver, Sender};
+pub use message::{Message, MessageConfig, Meta};
+pub use typed::{TypedReceiver, TypedSender};
@@ -0,0 +1,247 @@
+//! Messages carrying metadata in the envelope mode.
+//!
+//! An exchange with `"envelope": true` attaches a [Meta] to every
+//! message, including the send time, the sequence number counted by
+//! the sender, the producing processor and user headers. The
+//! transports carry the metadata in their own ways. The file and
+//! Unix socket transports prefix the payload with a frame header,
+//! zenoh uses attachments and AMQP uses message properties.
+
+use crate::common::*;
+use anyhow::{ensure, Context as _};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// The prefix of reserved header names.
+const RESERVED_PREFIX: &str = "easyflow-";
+pub(crate) const TIMESTAMP_KEY: &str = "easyflow-timestamp";
+pub(crate) const SEQ_KEY: &str = "easyflow-seq";
+pub(crate) const PRODUCER_KEY: &str = "easyflow-producer";
+
+/// A received payload along with its metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub payload: Vec<u8>,
+    /// The metadata of the message. It is `None` if the exchange is
+    /// not in the envelope mode.
+    pub meta: Option<Meta>,
+}
+
+impl Message {
+    pub(crate) fn bare(payload: Vec<u8>) -> Self {
+        Self {
+            payload,
+            meta: None,
+        }
+    }
+}
+
+/// The metadata attached to a message in the envelope mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Meta {
+    /// The time when the message is sent.
+    pub timestamp: DateTime<Local>,
+    /// The sequence number counted by the sender, starting from zero.
+    pub seq: u64,
+    /// The processor sending the message, if the sender is built from
+    /// a dataflow.
+    pub producer: Option<String>,
+    /// User defined headers.
+    pub headers: BTreeMap<String, String>,
+}
+
+impl Meta {
+    /// List the metadata as key-value pairs. Reserved keys start with
+    /// `easyflow-`, while user headers keep their names.
+    pub(crate) fn to_fields(&self) -> Vec<(String, String)> {
+        let mut fields = vec![
+            (TIMESTAMP_KEY.to_string(), format_timestamp(&self.timestamp)),
+            (SEQ_KEY.to_string(), self.seq.to_string()),
+        ];
+        if let Some(producer) = &self.producer {
+            fields.push((PRODUCER_KEY.to_string(), producer.clone()));
+        }
+        fields.extend(
+            self.headers
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+        fields
+    }
+
+    /// Collect the metadata from key-value pairs written by
+    /// [Meta::to_fields].
+    pub(crate) fn from_fields(fields: impl IntoIterator<Item = (String, String)>) -> Result<Self> {
+        let mut timestamp = None;
+        let mut seq = None;
+        let mut producer = None;
+        let mut headers = BTreeMap::new();
+
+        for (key, value) in fields {
+            match key.as_str() {
+                TIMESTAMP_KEY => timestamp = Some(parse_timestamp(&value)?),
+                SEQ_KEY => {
+                    let value: u64 = value
+                        .parse()
+                        .with_context(|| format!("invalid sequence number '{value}'"))?;
+                    seq = Some(value);
+                }
+                PRODUCER_KEY => producer = Some(value),
+                _ => {
+                    headers.insert(key, value);
+                }
+            }
+        }
+
+        Ok(Self {
+            timestamp: timestamp.context("the message timestamp is missing")?,
+            seq: seq.context("the message sequence number is missing")?,
+            producer,
+            headers,
+        })
+    }
+}
+
+/// The message format options shared by all transports. They are
+/// written along with the transport fields of an exchange.
+///
+/// ```json5
+/// "FRAME": {
+///     "type": "zenoh",
+///     "key": "frame",
+///     "envelope": true,
+///     "codec": "json",
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct MessageConfig {
+    /// Attach the send time, the sequence number, the producer and
+    /// headers to every message.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub envelope: bool,
+    /// The message encoding of typed senders and receivers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codec: Option<Codec>,
+    /// The protobuf message type name, such as `sensors.LidarPacket`.
+    /// The field is ignored without the `protobuf` feature.
+    #[cfg(feature = "protobuf")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_type: Option<String>,
+}
+
+/// Generates the metadata for each message sent by a sender.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Stamper {
+    producer: Option<String>,
+    next_seq: Arc<AtomicU64>,
+}
+
+impl Stamper {
+    pub fn set_producer(&mut self, producer: impl Into<String>) {
+        self.producer = Some(producer.into());
+    }
+
+    pub fn stamp(&self, headers: BTreeMap<String, String>) -> Result<Meta> {
+        check_headers(&headers)?;
+        Ok(Meta {
+            timestamp: Local::now(),
+            seq: self.next_seq.fetch_add(1, Ordering::SeqCst),
+            producer: self.producer.clone(),
+            headers,
+        })
+    }
+}
+
+/// Fail if headers are given to a sender not in the envelope mode.
+pub(crate) fn ensure_no_headers(headers: &BTreeMap<String, String>) -> Result<()> {
+    ensure!(
+        headers.is_empty(),
+        "headers are only supported by exchanges in the envelope mode"
+    );
+    Ok(())
+}
+
+/// Frame the payload for byte transports. It is returned as is if
+/// the sender is not in the envelope mode.
+pub(crate) fn frame<'a>(
+    envelope: Option<&Stamper>,
+    headers: BTreeMap<String, String>,
+    payload: &'a [u8],
+) -> Result<Cow<'a, [u8]>> {
+    match envelope {
+        Some(stamper) => {
+            let meta = stamper.stamp(headers)?;
+            Ok(encode_frame(&meta, payload).into())
+        }
+        None => {
+            ensure_no_headers(&headers)?;
+            Ok(payload.into())
+        }
+    }
+}
+
+/// Undo [frame] on the receiving side.
+pub(crate) fn unframe(envelope: bool, data: Vec<u8>) -> Result<Message> {
+    if envelope {
+        decode_frame(data)
+    } else {
+        Ok(Message::bare(data))
+    }
+}
+
+/// Prefix the payload with the metadata. The frame consists of the
+/// length of the metadata in 4-byte little endian, the metadata in
+/// JSON and the payload.
+pub(crate) fn encode_frame(meta: &Meta, payload: &[u8]) -> Vec<u8> {
+    let fields: BTreeMap<_, _> = meta.to_fields().into_iter().collect();
+    let header = serde_json::to_vec(&fields).unwrap();
+
+    let mut frame = Vec::with_capacity(4 + header.len() + payload.len());
+    frame.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&header);
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Split a frame written by [encode_frame] into the metadata and the
+/// payload.
+pub(crate) fn decode_frame(mut frame: Vec<u8>) -> Result<Message> {
+    ensure!(frame.len() >= 4, "the message frame is truncated");
+    let len = u32::from_le_bytes(frame[..4].try_into().unwrap()) as usize;
+    ensure!(frame.len() >= 4 + len, "the message frame is truncated");
+
+    let fields: BTreeMap<String, String> = serde_json::from_slice(&frame[4..(4 + len)])
+        .context("unable to parse the message metadata")?;
+    let meta = Meta::from_fields(fields)?;
+    let payload = frame.split_off(4 + len);
+
+    Ok(Message {
+        payload,
+        meta: Some(meta),
+    })
+}
+
+pub(crate) fn format_timestamp(timestamp: &DateTime<Local>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Nanos, false)
+}
+
+pub(crate) fn parse_timestamp(text: &str) -> Result<DateTime<Local>> {
+    let timestamp = DateTime::parse_from_rfc3339(text)
+        .with_context(|| format!("invalid message timestamp '{text}'"))?;
+    Ok(timestamp.with_timezone(&Local))
+}
+
+fn check_headers(headers: &BTreeMap<String, String>) -> Result<()> {
+    for key in headers.keys() {
+        ensure!(
+            !key.starts_with(RESERVED_PREFIX),
+            "the header name '{key}' is reserved"
+        );
+    }
+    Ok(())
+}
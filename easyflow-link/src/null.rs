@@ -1,12 +1,19 @@
-use crate::common::*;
+use crate::{
+    common::*,
+    message::{self, Message, MessageConfig, Stamper},
+};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
     #[serde(default = "default_recv")]
     pub recv: ReceiverKind,
+    /// The message format shared by all transports.
+    #[serde(flatten)]
+    pub message: MessageConfig,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ReceiverKind {
     Empty,
@@ -20,7 +27,9 @@ fn default_recv() -> ReceiverKind {
 
 impl Config {
     pub fn build_sender(&self) -> Sender {
-        Sender { _private: [] }
+        Sender {
+            envelope: self.message.envelope.then(Stamper::default),
+        }
     }
 
     pub fn build_receiver(&self) -> Receiver {
@@ -30,11 +39,34 @@ impl Config {
 
 #[derive(Debug)]
 pub struct Sender {
-    _private: [u8; 0],
+    envelope: Option<Stamper>,
 }
 
 impl Sender {
-    pub fn send(&self, _payload: &[u8]) {}
+    pub fn send(&self, _payload: &[u8]) {
+        // Count the message in the envelope mode. It cannot fail
+        // without headers.
+        if let Some(stamper) = &self.envelope {
+            let _ = stamper.stamp(BTreeMap::new());
+        }
+    }
+
+    pub fn send_with_headers(
+        &self,
+        _payload: &[u8],
+        headers: BTreeMap<String, String>,
+    ) -> Result<()> {
+        match &self.envelope {
+            Some(stamper) => stamper.stamp(headers).map(|_| ()),
+            None => message::ensure_no_headers(&headers),
+        }
+    }
+
+    pub fn set_producer(&mut self, producer: impl Into<String>) {
+        if let Some(stamper) = &mut self.envelope {
+            stamper.set_producer(producer);
+        }
+    }
 
     pub fn into_sink(self) -> impl Sink<Vec<u8>, Error = Error> {
         sink::unfold(self, |sender, payload: Vec<u8>| async move {
@@ -50,6 +82,11 @@ pub struct Receiver {
 }
 
 impl Receiver {
+    pub async fn recv_message(&self) -> Result<Option<Message>> {
+        let payload = self.recv().await?;
+        Ok(payload.map(Message::bare))
+    }
+
     pub async fn recv(&self) -> Result<Option<Vec<u8>>> {
         match self.recv {
             ReceiverKind::Empty => Ok(None),
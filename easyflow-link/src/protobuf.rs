@@ -0,0 +1,148 @@
+#![cfg(feature = "protobuf")]
+
+//! Senders and receivers of protobuf messages.
+//!
+//! The message type can be declared by the `message_type` field of an
+//! exchange. Senders and receivers built by
+//! [build_protobuf_sender](generic::Config::build_protobuf_sender) and
+//! [build_protobuf_receiver](generic::Config::build_protobuf_receiver)
+//! refuse to work with other message types.
+//!
+//! ```json5
+//! "LIDAR": {
+//!     "type": "zenoh",
+//!     "key": "lidar",
+//!     "message_type": "sensors.LidarPacket",
+//! }
+//! ```
+
+use crate::{common::*, generic};
+use anyhow::{ensure, Context as _};
+use derivative::Derivative;
+pub use prost;
+use prost::{Message, Name};
+use std::{any, marker::PhantomData};
+
+/// A sender that encodes protobuf messages of type `T`.
+#[derive(Derivative)]
+#[derivative(Debug(bound = ""))]
+pub struct Sender<T> {
+    sender: generic::Sender,
+    #[derivative(Debug = "ignore")]
+    _phantom: PhantomData<fn(T)>,
+}
+
+impl<T> Sender<T>
+where
+    T: Message,
+{
+    pub fn new(sender: generic::Sender) -> Self {
+        Self {
+            sender,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub async fn send(&mut self, message: &T) -> Result<()> {
+        self.sender.send(message.encode_to_vec()).await
+    }
+
+    /// Get the underlying sender of raw bytes.
+    pub fn into_inner(self) -> generic::Sender {
+        self.sender
+    }
+
+    pub fn into_sink(self) -> impl Sink<T, Error = Error> {
+        sink::unfold(self, |mut sender, message: T| async move {
+            sender.send(&message).await.map(|_| sender)
+        })
+    }
+}
+
+/// A receiver that decodes protobuf messages of type `T`.
+#[derive(Derivative)]
+#[derivative(Debug(bound = ""))]
+pub struct Receiver<T> {
+    receiver: generic::Receiver,
+    #[derivative(Debug = "ignore")]
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Receiver<T>
+where
+    T: Message + Default,
+{
+    pub fn new(receiver: generic::Receiver) -> Self {
+        Self {
+            receiver,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub async fn recv(&mut self) -> Result<Option<T>> {
+        let Some(payload) = self.receiver.recv().await? else {
+            return Ok(None);
+        };
+        let message = T::decode(payload.as_slice()).with_context(|| {
+            format!(
+                "unable to decode protobuf message '{}'",
+                any::type_name::<T>()
+            )
+        })?;
+        Ok(Some(message))
+    }
+
+    /// Get the underlying receiver of raw bytes.
+    pub fn into_inner(self) -> generic::Receiver {
+        self.receiver
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = Result<T>> {
+        stream::try_unfold(self, |mut rx| async move {
+            let item = rx.recv().await?;
+            anyhow::Ok(item.map(|item| (item, rx)))
+        })
+    }
+}
+
+impl generic::Config {
+    /// Build a sender of protobuf messages of type `T`.
+    ///
+    /// It fails if the exchange declares another message type.
+    pub async fn build_protobuf_sender<T>(&self) -> Result<Sender<T>>
+    where
+        T: Message + Name,
+    {
+        check_message_type::<T>(self.message_config().message_type.as_deref())?;
+        Ok(self.build_sender().await?.into_protobuf_encoded())
+    }
+
+    /// Build a receiver of protobuf messages of type `T`.
+    ///
+    /// It fails if the exchange declares another message type.
+    pub async fn build_protobuf_receiver<T>(&self) -> Result<Receiver<T>>
+    where
+        T: Message + Name + Default,
+    {
+        check_message_type::<T>(self.message_config().message_type.as_deref())?;
+        Ok(self.build_receiver().await?.into_protobuf_decoded())
+    }
+}
+
+/// Check that `T` is the message type declared by an exchange. Any
+/// type is accepted if the exchange declares none.
+pub fn check_message_type<T>(declared: Option<&str>) -> Result<()>
+where
+    T: Name,
+{
+    if let Some(declared) = declared {
+        let name = T::full_name();
+        ensure!(
+            declared == name,
+            "expect protobuf message type '{}', but '{}' is used",
+            declared,
+            name
+        );
+    }
+    Ok(())
+}
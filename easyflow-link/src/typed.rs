@@ -0,0 +1,128 @@
+use crate::{codec::Codec, common::*, message::Meta, Receiver, Sender};
+use anyhow::Context as _;
+use derivative::Derivative;
+use serde::de::DeserializeOwned;
+use std::{collections::BTreeMap, marker::PhantomData};
+
+/// A sender that encodes values of type `T` by a [Codec].
+#[derive(Derivative)]
+#[derivative(Debug(bound = ""))]
+pub struct TypedSender<T> {
+    sender: Sender,
+    codec: Codec,
+    exchange: String,
+    #[derivative(Debug = "ignore")]
+    _phantom: PhantomData<fn(T)>,
+}
+
+impl<T> TypedSender<T>
+where
+    T: Serialize,
+{
+    /// Wrap a sender. The `exchange` name is shown in error messages.
+    pub fn new(sender: Sender, codec: Codec, exchange: impl Into<String>) -> Self {
+        Self {
+            sender,
+            codec,
+            exchange: exchange.into(),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub async fn send(&mut self, value: &T) -> Result<()> {
+        self.send_with_headers(value, BTreeMap::new()).await
+    }
+
+    /// Send a value with user headers. See
+    /// [Sender::send_with_headers].
+    pub async fn send_with_headers(
+        &mut self,
+        value: &T,
+        headers: BTreeMap<String, String>,
+    ) -> Result<()> {
+        let payload = self.codec.encode(value).with_context(|| {
+            format!(
+                "unable to encode {} message to exchange '{}'",
+                self.codec, self.exchange
+            )
+        })?;
+        self.sender.send_with_headers(payload, headers).await
+    }
+
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    /// Get the underlying sender of raw bytes.
+    pub fn into_inner(self) -> Sender {
+        self.sender
+    }
+
+    pub fn into_sink(self) -> impl Sink<T, Error = Error> {
+        sink::unfold(self, |mut sender, value: T| async move {
+            sender.send(&value).await.map(|_| sender)
+        })
+    }
+}
+
+/// A receiver that decodes values of type `T` by a [Codec].
+#[derive(Derivative)]
+#[derivative(Debug(bound = ""))]
+pub struct TypedReceiver<T> {
+    receiver: Receiver,
+    codec: Codec,
+    exchange: String,
+    #[derivative(Debug = "ignore")]
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> TypedReceiver<T>
+where
+    T: DeserializeOwned,
+{
+    /// Wrap a receiver. The `exchange` name is shown in error messages.
+    pub fn new(receiver: Receiver, codec: Codec, exchange: impl Into<String>) -> Self {
+        Self {
+            receiver,
+            codec,
+            exchange: exchange.into(),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub async fn recv(&mut self) -> Result<Option<T>> {
+        let item = self.recv_with_meta().await?;
+        Ok(item.map(|(value, _)| value))
+    }
+
+    /// Receive a value along with its metadata. The metadata is
+    /// `None` unless the exchange is in the envelope mode.
+    pub async fn recv_with_meta(&mut self) -> Result<Option<(T, Option<Meta>)>> {
+        let Some(message) = self.receiver.recv_message().await? else {
+            return Ok(None);
+        };
+        let value = self.codec.decode(&message.payload).with_context(|| {
+            format!(
+                "unable to decode {} message from exchange '{}'",
+                self.codec, self.exchange
+            )
+        })?;
+        Ok(Some((value, message.meta)))
+    }
+
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    /// Get the underlying receiver of raw bytes.
+    pub fn into_inner(self) -> Receiver {
+        self.receiver
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = Result<T>> {
+        stream::try_unfold(self, |mut rx| async move {
+            let item = rx.recv().await?;
+            anyhow::Ok(item.map(|item| (item, rx)))
+        })
+    }
+}
@@ -1,20 +1,27 @@
 #![cfg(feature = "unix-sock")]
 #![cfg(unix)]
 
-use crate::common::*;
+use crate::{
+    common::*,
+    message::{self, Message, MessageConfig, Stamper},
+};
 use anyhow::{ensure, Context};
 use async_std::os::unix::net::{UnixListener, UnixStream};
 use derivative::Derivative;
 use log::{debug, error};
-use std::{fs, pin::Pin, time::Instant};
+use std::{collections::BTreeMap, fs, pin::Pin, time::Instant};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
     pub path: PathBuf,
     #[serde(default = "default_force")]
     pub force: bool,
     #[serde(with = "humantime_serde", default)]
+    #[schemars(with = "Option<String>")]
     pub connect_timeout: Option<Duration>,
+    /// The message format shared by all transports.
+    #[serde(flatten)]
+    pub message: MessageConfig,
 }
 
 fn default_force() -> bool {
@@ -56,7 +63,10 @@ impl Config {
             self.path.display()
         );
 
-        Ok(Sender { stream })
+        Ok(Sender {
+            stream,
+            envelope: self.message.envelope.then(Stamper::default),
+        })
     }
 
     pub async fn build_receiver(&self) -> Result<Receiver> {
@@ -143,6 +153,7 @@ impl Config {
         Ok(Receiver {
             path: self.path.clone(),
             stream,
+            envelope: self.message.envelope,
         })
     }
 }
@@ -150,16 +161,32 @@ impl Config {
 #[derive(Debug)]
 pub struct Sender {
     stream: UnixStream,
+    envelope: Option<Stamper>,
 }
 
 impl Sender {
     pub async fn send(&mut self, payload: &[u8]) -> Result<()> {
-        let len = payload.len() as u64;
+        self.send_with_headers(payload, BTreeMap::new()).await
+    }
+
+    pub async fn send_with_headers(
+        &mut self,
+        payload: &[u8],
+        headers: BTreeMap<String, String>,
+    ) -> Result<()> {
+        let data = message::frame(self.envelope.as_ref(), headers, payload)?;
+        let len = data.len() as u64;
         self.stream.write_all(&len.to_le_bytes()).await?;
-        self.stream.write_all(payload).await?;
+        self.stream.write_all(&data).await?;
         Ok(())
     }
 
+    pub fn set_producer(&mut self, producer: impl Into<String>) {
+        if let Some(stamper) = &mut self.envelope {
+            stamper.set_producer(producer);
+        }
+    }
+
     pub fn into_sink(self) -> impl Sink<Vec<u8>, Error = Error> {
         sink::unfold(self, |mut sender, payload: Vec<u8>| async move {
             sender.send(&payload).await.map(|_| sender)
@@ -173,11 +200,20 @@ pub struct Receiver {
     path: PathBuf,
     #[derivative(Debug = "ignore")]
     stream: Pin<Box<dyn Stream<Item = Result<Vec<u8>>> + Send>>,
+    envelope: bool,
 }
 
 impl Receiver {
     pub async fn recv(&mut self) -> Result<Option<Vec<u8>>> {
-        self.stream.next().await.transpose()
+        let message = self.recv_message().await?;
+        Ok(message.map(|message| message.payload))
+    }
+
+    pub async fn recv_message(&mut self) -> Result<Option<Message>> {
+        let Some(data) = self.stream.next().await.transpose()? else {
+            return Ok(None);
+        };
+        Ok(Some(message::unframe(self.envelope, data)?))
     }
 
     pub fn into_stream(self) -> impl Stream<Item = Result<Vec<u8>>> {
@@ -215,6 +251,7 @@ mod tests {
             path,
             force: false,
             connect_timeout: None,
+            message: MessageConfig::default(),
         };
         let mut rx = config.build_receiver().await?;
         let mut tx = config.build_sender().await?;
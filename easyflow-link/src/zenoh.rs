@@ -1,11 +1,15 @@
 #![cfg(feature = "zenoh")]
 
-use anyhow::{anyhow, Result};
+use crate::message::{self, Message, MessageConfig, Meta, Stamper};
+use anyhow::{anyhow, Context as _, Result};
 use futures::{sink, stream, Sink, Stream};
 use global::SESSION;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use zenoh::{prelude::r#async::*, publication::Publisher, subscriber::Subscriber};
+use std::{collections::BTreeMap, sync::Arc};
+use zenoh::{
+    prelude::r#async::*, publication::Publisher, sample::Attachment, subscriber::Subscriber,
+};
 
 mod global {
     use std::sync::Arc;
@@ -22,9 +26,12 @@ mod global {
             .into_arc()
     });
 }
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
     pub key: String,
+    /// The message format shared by all transports.
+    #[serde(flatten)]
+    pub message: MessageConfig,
 }
 
 impl Config {
@@ -38,7 +45,10 @@ impl Config {
             .await
             .map_err(map_err)?;
 
-        Ok(Sender { publisher })
+        Ok(Sender {
+            publisher,
+            envelope: self.message.envelope.then(Stamper::default),
+        })
     }
 
     pub async fn build_receiver(&self) -> Result<Receiver> {
@@ -51,18 +61,53 @@ impl Config {
             .await
             .map_err(map_err)?;
 
-        Ok(Receiver { subscriber })
+        Ok(Receiver {
+            subscriber,
+            envelope: self.message.envelope,
+        })
     }
 }
 
 #[derive(Debug)]
 pub struct Sender {
     publisher: Publisher<'static>,
+    envelope: Option<Stamper>,
 }
 
 impl Sender {
     pub async fn send(&self, payload: &[u8]) -> Result<()> {
-        self.publisher.put(payload).res().await.map_err(map_err)
+        self.send_with_headers(payload, BTreeMap::new()).await
+    }
+
+    /// Send a message. In the envelope mode, the metadata is carried
+    /// in the attachment of the sample.
+    pub async fn send_with_headers(
+        &self,
+        payload: &[u8],
+        headers: BTreeMap<String, String>,
+    ) -> Result<()> {
+        let publication = self.publisher.put(payload);
+        let publication = match &self.envelope {
+            Some(stamper) => {
+                let meta = stamper.stamp(headers)?;
+                let mut attachment = Attachment::new();
+                for (key, value) in meta.to_fields() {
+                    attachment.insert(&key, &value);
+                }
+                publication.with_attachment(attachment)
+            }
+            None => {
+                message::ensure_no_headers(&headers)?;
+                publication
+            }
+        };
+        publication.res().await.map_err(map_err)
+    }
+
+    pub fn set_producer(&mut self, producer: impl Into<String>) {
+        if let Some(stamper) = &mut self.envelope {
+            stamper.set_producer(producer);
+        }
     }
 
     pub fn into_sink(self) -> impl Sink<Vec<u8>, Error = anyhow::Error> {
@@ -75,15 +120,39 @@ impl Sender {
 #[derive(Debug)]
 pub struct Receiver {
     subscriber: Subscriber<'static, flume::Receiver<Sample>>,
+    envelope: bool,
 }
 
 impl Receiver {
     pub async fn recv(&mut self) -> Result<Option<Vec<u8>>> {
+        let message = self.recv_message().await?;
+        Ok(message.map(|message| message.payload))
+    }
+
+    pub async fn recv_message(&mut self) -> Result<Option<Message>> {
         let Ok(sample) = self.subscriber.recv_async().await else {
             return Ok(None);
         };
+
+        let meta = if self.envelope {
+            let attachment = sample
+                .attachment()
+                .context("the message attachment is missing")?;
+            let fields: Vec<_> = attachment
+                .iter()
+                .map(|(key, value)| {
+                    let key = String::from_utf8(key.as_slice().to_vec())?;
+                    let value = String::from_utf8(value.as_slice().to_vec())?;
+                    anyhow::Ok((key, value))
+                })
+                .collect::<Result<_>>()?;
+            Some(Meta::from_fields(fields)?)
+        } else {
+            None
+        };
+
         let payload: Vec<_> = sample.value.try_into()?;
-        Ok(Some(payload))
+        Ok(Some(Message { payload, meta }))
     }
 
     pub fn into_stream(self) -> impl Stream<Item = Result<Vec<u8>>> {
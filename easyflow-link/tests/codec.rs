@@ -0,0 +1,34 @@
+use easyflow_link::Codec;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Packet {
+    seq: u64,
+    name: String,
+    points: Vec<[f32; 3]>,
+    label: Option<String>,
+}
+
+#[test]
+fn codec_round_trip_test() {
+    let packet = Packet {
+        seq: 42,
+        name: "lidar".to_string(),
+        points: vec![[0.0, 1.0, 2.0], [3.5, -4.0, 5.25]],
+        label: None,
+    };
+
+    for codec in [Codec::Bincode, Codec::Json, Codec::MessagePack, Codec::Cbor] {
+        let bytes = codec.encode(&packet).unwrap();
+        let decoded: Packet = codec.decode(&bytes).unwrap();
+        assert_eq!(decoded, packet, "{} round trip", codec);
+    }
+}
+
+#[test]
+fn codec_name_test() {
+    let codec: Codec = serde_json::from_str(r#""msgpack""#).unwrap();
+    assert_eq!(codec, Codec::MessagePack);
+    assert_eq!(codec.to_string(), "msgpack");
+    assert_eq!(Codec::default(), Codec::Bincode);
+}
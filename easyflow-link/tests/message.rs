@@ -0,0 +1,163 @@
+use anyhow::Result;
+use easyflow_link::Config;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+fn headers(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[tokio::test]
+async fn file_envelope_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let config: Config = serde_json::from_value(json!({
+        "type": "file",
+        "dir": dir.path(),
+        "envelope": true,
+    }))?;
+    assert!(config.message_config().envelope);
+
+    let mut sender = config.build_sender().await?;
+    sender.set_producer("camera");
+    for index in 0..3 {
+        let value = index.to_string();
+        sender
+            .send_with_headers(value.as_bytes(), headers(&[("frame", &value)]))
+            .await?;
+    }
+
+    let mut receiver = config.build_receiver().await?;
+    let mut last_timestamp = None;
+    for index in 0..3 {
+        let message = receiver.recv_message().await?.unwrap();
+        assert_eq!(message.payload, index.to_string().as_bytes());
+
+        let meta = message.meta.unwrap();
+        assert_eq!(meta.seq, index);
+        assert_eq!(meta.producer.as_deref(), Some("camera"));
+        assert_eq!(meta.headers, headers(&[("frame", &index.to_string())]));
+        assert!(last_timestamp <= Some(meta.timestamp));
+        last_timestamp = Some(meta.timestamp);
+    }
+    assert!(receiver.recv_message().await?.is_none());
+
+    // plain receivers strip the envelope
+    let mut receiver = config.build_receiver().await?;
+    assert_eq!(receiver.recv().await?.as_deref(), Some(&b"0"[..]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn file_bare_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let config: Config = serde_json::from_value(json!({
+        "type": "file",
+        "dir": dir.path(),
+    }))?;
+    assert!(!config.message_config().envelope);
+
+    let mut sender = config.build_sender().await?;
+    sender.send(&b"payload"[..]).await?;
+    let err = sender
+        .send_with_headers(&b"payload"[..], headers(&[("frame", "0")]))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "headers are only supported by exchanges in the envelope mode"
+    );
+
+    let mut receiver = config.build_receiver().await?;
+    let message = receiver.recv_message().await?.unwrap();
+    assert_eq!(message.payload, b"payload");
+    assert_eq!(message.meta, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn reserved_header_test() -> Result<()> {
+    let config: Config = serde_json::from_value(json!({
+        "type": "null",
+        "envelope": true,
+    }))?;
+
+    let mut sender = config.build_sender().await?;
+    sender
+        .send_with_headers(&b"payload"[..], headers(&[("frame", "0")]))
+        .await?;
+    let err = sender
+        .send_with_headers(&b"payload"[..], headers(&[("easyflow-seq", "0")]))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "the header name 'easyflow-seq' is reserved"
+    );
+
+    Ok(())
+}
+
+#[cfg(all(unix, feature = "unix-sock"))]
+#[tokio::test]
+async fn unix_envelope_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let config: Config = serde_json::from_value(json!({
+        "type": "unix",
+        "path": dir.path().join("test.socket"),
+        "envelope": true,
+    }))?;
+
+    let mut receiver = config.build_receiver().await?;
+    let mut sender = config.build_sender().await?;
+    sender.set_producer("lidar");
+    sender.send(&b"first"[..]).await?;
+    sender
+        .send_with_headers(&b"second"[..], headers(&[("unit", "m")]))
+        .await?;
+
+    let first = receiver.recv_message().await?.unwrap();
+    assert_eq!(first.payload, b"first");
+    let meta = first.meta.unwrap();
+    assert_eq!(meta.seq, 0);
+    assert_eq!(meta.producer.as_deref(), Some("lidar"));
+    assert!(meta.headers.is_empty());
+
+    let second = receiver.recv_message().await?.unwrap();
+    assert_eq!(second.payload, b"second");
+    let meta = second.meta.unwrap();
+    assert_eq!(meta.seq, 1);
+    assert_eq!(meta.headers, headers(&[("unit", "m")]));
+
+    Ok(())
+}
+
+#[cfg(feature = "zenoh")]
+#[tokio::test]
+async fn zenoh_envelope_test() -> Result<()> {
+    let config: Config = serde_json::from_value(json!({
+        "type": "zenoh",
+        "key": "easyflow/test/envelope",
+        "envelope": true,
+    }))?;
+
+    let mut receiver = config.build_receiver().await?;
+    let mut sender = config.build_sender().await?;
+    sender.set_producer("radar");
+    sender
+        .send_with_headers(&b"payload"[..], headers(&[("unit", "m")]))
+        .await?;
+
+    let message = receiver.recv_message().await?.unwrap();
+    assert_eq!(message.payload, b"payload");
+    let meta = message.meta.unwrap();
+    assert_eq!(meta.seq, 0);
+    assert_eq!(meta.producer.as_deref(), Some("radar"));
+    assert_eq!(meta.headers, headers(&[("unit", "m")]));
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "protobuf")]
+
+use anyhow::Result;
+use easyflow_link::Config;
+use futures::{SinkExt as _, TryStreamExt as _};
+use prost::{Message, Name};
+use serde_json::json;
+
+#[derive(Clone, PartialEq, Message)]
+struct Point {
+    #[prost(float, tag = "1")]
+    x: f32,
+    #[prost(float, tag = "2")]
+    y: f32,
+    #[prost(string, tag = "3")]
+    label: String,
+}
+
+impl Name for Point {
+    const NAME: &'static str = "Point";
+    const PACKAGE: &'static str = "test";
+}
+
+fn file_config(dir: &std::path::Path, message_type: &str) -> Config {
+    serde_json::from_value(json!({
+        "type": "file",
+        "dir": dir,
+        "message_type": message_type,
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn protobuf_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let config = file_config(dir.path(), "test.Point");
+
+    let points = vec![
+        Point {
+            x: 1.0,
+            y: 2.0,
+            label: "first".to_string(),
+        },
+        Point {
+            x: -3.5,
+            y: 0.25,
+            label: "second".to_string(),
+        },
+    ];
+
+    let sender = config.build_protobuf_sender::<Point>().await?.into_sink();
+    futures::pin_mut!(sender);
+    for point in &points {
+        sender.send(point.clone()).await?;
+    }
+
+    let receiver = config.build_protobuf_receiver::<Point>().await?;
+    let received: Vec<Point> = receiver.into_stream().try_collect().await?;
+    assert_eq!(received, points);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn protobuf_type_mismatch_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let config = file_config(dir.path(), "test.Other");
+
+    let err = config.build_protobuf_sender::<Point>().await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "expect protobuf message type 'test.Other', but 'test.Point' is used"
+    );
+
+    Ok(())
+}
@@ -1,37 +1,53 @@
-use crate::error::Error;
+use crate::{
+    diff::DataflowDiff,
+    error::Error,
+    merged::{ClosePolicy, MergedReceiver},
+    multi_sender::{FailurePolicy, MultiSender},
+    processor::ProcessorIo,
+};
 use anyhow::Result;
 use derivative::Derivative;
 use easyflow_config::{
-    Connection, Dir, GraphConfig, GraphUnchecked, Ident, IntoIdent, IntoKey, Key,
+    Connection, Diagnostic, Dir, GraphConfig, GraphUnchecked, IncludeStack, IntoKey, Key, Launch,
 };
-use easyflow_link::Config as Exchange;
+use easyflow_link::{Config as Exchange, TypedReceiver, TypedSender};
+use futures::future;
 use indexmap::{IndexMap, IndexSet};
 use itertools::{chain, Itertools};
 use ownref::{ArcOwnedC, ArcRefC};
-use serde::{Deserialize, Serialize};
-use serde_loader::Json5Path;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
-    fs::File,
+    fs::{self, File},
     io::{self, BufWriter},
     path::{Path, PathBuf},
 };
 
+#[cfg(feature = "protobuf")]
+use easyflow_link::protobuf::prost;
+
 type ARef<T> = ArcRefC<'static, GraphUnchecked, T>;
 type AOwned<T> = ArcOwnedC<'static, GraphUnchecked, T>;
 
-// TODO: serialize of graph to config..
 /// The graph data structure that describes the dataflow.
+///
+/// It is serialized to the configuration it is constructed from,
+/// which may refer to module files. Use
+/// [to_flattened_config](Dataflow::to_flattened_config) to obtain a
+/// self-contained configuration.
 #[derive(Debug, Clone, Serialize, Deserialize, Derivative)]
 #[serde(try_from = "GraphConfig", into = "GraphConfig")]
 #[derivative(PartialEq, Eq)]
 pub struct Dataflow {
     #[derivative(PartialEq = "ignore")]
-    bindings: ARef<IndexMap<PathBuf, Dir>>,
-    processors: ARef<IndexSet<Ident>>,
+    bindings: ARef<IndexMap<Dir, PathBuf>>,
+    processors: ARef<IndexSet<Key>>,
     exchanges: ARef<IndexMap<Key, Exchange>>,
     adj_exchange: ARef<IndexMap<Key, Connection>>,
-    adj_processor: HashMap<ARef<Ident>, ProcInOut>,
+    adj_processor: HashMap<ARef<Key>, ProcInOut>,
+    launch: ARef<IndexMap<Key, Launch>>,
+    #[derivative(PartialEq = "ignore")]
+    graph: ARef<GraphUnchecked>,
     #[derivative(PartialEq = "ignore")]
     config: GraphConfig,
 }
@@ -46,23 +62,92 @@ struct ProcInOut {
 }
 
 impl Dataflow {
-    /// Open a dataflow configuration file in JSON5 format.
+    /// Open a dataflow configuration file.
+    ///
+    /// The parser is chosen by the file extension: `.toml` for TOML,
+    /// `.yaml` or `.yml` for YAML, and JSON5 for others.
+    ///
+    /// Variables like `${VAR}` in the file and its modules are
+    /// substituted. See [GraphConfig] for the syntax.
     pub fn open<F>(file: F) -> Result<Self>
     where
         F: AsRef<Path>,
     {
-        Ok(Json5Path::open_and_take(file)?)
+        let config = GraphConfig::open(file)?;
+        Self::from_config(config)
+    }
+
+    /// Open a dataflow configuration file and apply overlay files on
+    /// top of it in order.
+    ///
+    /// Overlays replace or patch exchange configurations, add
+    /// processors and connections, or disable connections of the
+    /// flattened graph. See [Overlay](easyflow_config::Overlay) for the
+    /// format. The modified graph is validated again.
+    ///
+    /// The dataflow is serialized to the
+    /// [flattened configuration](Dataflow::to_flattened_config) with
+    /// the overlays applied.
+    pub fn open_with_overlays<F, I, P>(file: F, overlays: I) -> Result<Self>
+    where
+        F: AsRef<Path>,
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let config = GraphConfig::open(file)?;
+        let graph = config.clone().flatten_with_overlays(overlays)?;
+        let mut dataflow = Self::from_graph(config, graph);
+        dataflow.config = dataflow.to_flattened_config();
+        Ok(dataflow)
+    }
+
+    /// Open a dataflow configuration file, searching package modules
+    /// like `@sensors/camera.json5` in the directories of `search_path`.
+    ///
+    /// The directories are searched before the ones listed in the
+    /// [MODULE_PATH_ENV](easyflow_config::MODULE_PATH_ENV) environment
+    /// variable. The files where modules are found are recorded in
+    /// [bindings](Dataflow::bindings).
+    pub fn open_with_search_path<F, I, P>(file: F, search_path: I) -> Result<Self>
+    where
+        F: AsRef<Path>,
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        let config = GraphConfig::open(file)?;
+        let mut stack = IncludeStack::with_search_path(search_path);
+        let graph = config.clone().flatten_in::<_, &Path>(&mut stack, [])?;
+        Ok(Self::from_graph(config, graph))
+    }
+
+    /// Validate a dataflow configuration file and its modules.
+    ///
+    /// It reports every problem found instead of the first one. An
+    /// empty list is returned if the file can be opened by
+    /// [open](Dataflow::open). See [GraphConfig::check] for details.
+    pub fn check<F>(file: F) -> Vec<Diagnostic>
+    where
+        F: AsRef<Path>,
+    {
+        GraphConfig::check(file)
     }
 
     /// Construct a graph from configuration data.
     pub fn from_config(config: GraphConfig) -> Result<Self> {
-        let base = ARef::new(config.clone().flatten()?);
+        let graph = config.clone().flatten()?;
+        Ok(Self::from_graph(config, graph))
+    }
+
+    /// Construct from the configuration and the graph flattened from it.
+    fn from_graph(config: GraphConfig, graph: GraphUnchecked) -> Self {
+        let base = ARef::new(graph);
         let bindings = base.clone().map(|g| &g.bindings);
         let processors = base.clone().map(|g| &g.processors);
         let exchanges = base.clone().map(|g| &g.exchanges);
         let adj_exchange = base.clone().map(|g| &g.connections);
+        let launch = base.clone().map(|g| &g.launch);
 
-        let mut proc_to_sinks: HashMap<ARef<Ident>, HashSet<ARef<Key>>> =
+        let mut proc_to_sinks: HashMap<ARef<Key>, HashSet<ARef<Key>>> =
             ARef::into_arc_owned(adj_exchange.clone())
                 .flatten()
                 .flat_map(|pair| {
@@ -75,7 +160,7 @@ impl Dataflow {
                 })
                 .into_grouping_map()
                 .collect();
-        let mut proc_to_srcs: HashMap<ARef<Ident>, HashSet<ARef<Key>>> =
+        let mut proc_to_srcs: HashMap<ARef<Key>, HashSet<ARef<Key>>> =
             ARef::into_arc_owned(adj_exchange.clone())
                 .flatten()
                 .flat_map(|pair| {
@@ -104,14 +189,48 @@ impl Dataflow {
             })
             .collect();
 
-        Ok(Self {
+        Self {
             bindings,
             processors,
             exchanges,
             adj_exchange,
             adj_processor,
+            launch,
+            graph: base,
             config,
-        })
+        }
+    }
+
+    /// Build a self-contained configuration of the dataflow.
+    ///
+    /// Modules are inlined, processors and exchanges are declared by
+    /// their full keys, and `import` exchanges are replaced by the
+    /// imported configurations. The configuration constructs a
+    /// dataflow identical to this one.
+    pub fn to_flattened_config(&self) -> GraphConfig {
+        let graph = GraphUnchecked {
+            bindings: IndexMap::new(),
+            processors: (*self.processors).clone(),
+            exchanges: (*self.exchanges).clone(),
+            connections: (*self.adj_exchange).clone(),
+            launch: (*self.launch).clone(),
+            declarations: IndexMap::new(),
+        };
+        GraphConfig {
+            lints: self.config.lints.clone(),
+            ..graph.into_config()
+        }
+    }
+
+    /// Save the self-contained configuration of the dataflow to a file.
+    ///
+    /// The file format is chosen by the file extension. See
+    /// [to_flattened_config](Dataflow::to_flattened_config) for details.
+    pub fn save_flattened<F>(&self, file: F) -> Result<()>
+    where
+        F: AsRef<Path>,
+    {
+        self.to_flattened_config().save(file)
     }
 
     /// Save the dataflow graph to the GraphViz DOT file.
@@ -119,13 +238,104 @@ impl Dataflow {
     where
         F: AsRef<Path>,
     {
-        let mut writer = BufWriter::new(File::create(file)?);
-        dot::render(self, &mut writer)?;
+        fs::write(file, self.to_dot())
+    }
+
+    /// Save the dataflow graph to the GraphViz DOT file with the
+    /// rendering options.
+    pub fn save_dot_file_with<F>(&self, file: F, options: &DotOptions) -> Result<(), Error>
+    where
+        F: AsRef<Path>,
+    {
+        fs::write(file, self.to_dot_with(options)?)?;
+        Ok(())
+    }
+
+    /// Render the dataflow graph in the GraphViz DOT language.
+    ///
+    /// Processors and exchanges declared by a module are grouped in a
+    /// cluster named after the module. Exchanges are labelled with
+    /// their endpoints and colored by the transport type.
+    pub fn to_dot(&self) -> String {
+        graphviz::render_dot(self, None)
+    }
+
+    /// Render the dataflow graph in the GraphViz DOT language with
+    /// the rendering options.
+    pub fn to_dot_with(&self, options: &DotOptions) -> Result<String, Error> {
+        let nodes = match &options.neighborhood {
+            Some(proc) => Some(graphviz::neighborhood(self, proc, options.radius)?),
+            None => None,
+        };
+        Ok(graphviz::render_dot(self, nodes.as_ref()))
+    }
+
+    /// Save the dataflow graph to a Mermaid flowchart file.
+    pub fn save_mermaid_file<F>(&self, file: F) -> io::Result<()>
+    where
+        F: AsRef<Path>,
+    {
+        fs::write(file, self.to_mermaid())
+    }
+
+    /// Render the dataflow graph as a Mermaid flowchart.
+    ///
+    /// Processors are drawn as boxes and exchanges as hexagons, the
+    /// same as the DOT output.
+    pub fn to_mermaid(&self) -> String {
+        graphviz::render_mermaid(self)
+    }
+
+    /// Save the [adjacency](Dataflow::adjacency) of the dataflow
+    /// graph to a JSON file.
+    pub fn save_adjacency_file<F>(&self, file: F) -> io::Result<()>
+    where
+        F: AsRef<Path>,
+    {
+        let writer = BufWriter::new(File::create(file)?);
+        serde_json::to_writer_pretty(writer, &self.adjacency())?;
         Ok(())
     }
 
+    /// Get the nodes and edges of the dataflow graph in a
+    /// machine-readable form.
+    pub fn adjacency(&self) -> Adjacency {
+        graphviz::adjacency(self)
+    }
+
+    /// Convert the dataflow into a [petgraph] directed graph.
+    ///
+    /// Nodes are processors and exchanges. Edges go in the direction
+    /// of the data flow, that is, from a processor to an exchange it
+    /// sends to, and from an exchange to a processor it receives from.
+    #[cfg(feature = "petgraph")]
+    pub fn to_petgraph(&self) -> petgraph::graph::DiGraph<AdjacencyNode, EdgeDirection> {
+        let Adjacency { nodes, edges } = self.adjacency();
+        let mut graph = petgraph::graph::DiGraph::with_capacity(nodes.len(), edges.len());
+
+        let mut indices = HashMap::new();
+        for node in nodes {
+            let id = (node.kind, node.key.clone());
+            indices.insert(id, graph.add_node(node));
+        }
+
+        for edge in edges {
+            let proc = indices[&(NodeKind::Processor, edge.processor)];
+            let exchange = indices[&(NodeKind::Exchange, edge.exchange)];
+            match edge.direction {
+                EdgeDirection::Input => graph.add_edge(exchange, proc, edge.direction),
+                EdgeDirection::Output => graph.add_edge(proc, exchange, edge.direction),
+            };
+        }
+
+        graph
+    }
+
     /// Get the names of declared processors.
-    pub fn processors(&self) -> &IndexSet<Ident> {
+    ///
+    /// Processors declared in scoped module namespaces are prefixed
+    /// by the module names.
+    pub fn processors(&self) -> &IndexSet<Key> {
         &self.processors
     }
 
@@ -134,11 +344,269 @@ impl Dataflow {
         &self.exchanges
     }
 
+    /// Run the lints configured in the dataflow file.
+    ///
+    /// It reports exchanges without producers or consumers and
+    /// processors without connections. See [Lint](easyflow_config::Lint)
+    /// for the list of lints.
+    pub fn lint(&self) -> Vec<Diagnostic> {
+        self.graph.lint(&self.config.lints)
+    }
+
+    /// Get the connected processors of each exchange.
+    pub fn connections(&self) -> &IndexMap<Key, Connection> {
+        &self.adj_exchange
+    }
+
+    /// Compare this dataflow to a `new` one.
+    pub fn diff(&self, new: &Dataflow) -> DataflowDiff {
+        DataflowDiff::new(self, new)
+    }
+
+    /// Get the launch specs of processors.
+    ///
+    /// Processors without launch specs are not included.
+    pub fn launches(&self) -> &IndexMap<Key, Launch> {
+        &self.launch
+    }
+
+    /// Get all processors in the order to be started.
+    ///
+    /// A processor that consumes the outputs of another processor comes
+    /// before it, so that no messages are sent before the consumers are
+    /// ready. Processors in a cycle are placed in the declaration order.
+    pub fn launch_order(&self) -> Vec<&Key> {
+        let (mut order, cyclic) = self.consumer_first_order();
+        order.extend(cyclic);
+        order
+    }
+
+    /// Sort processors so that consumers come before producers. The
+    /// processors that cannot be sorted due to cycles are returned
+    /// separately in the declaration order.
+    fn consumer_first_order(&self) -> (Vec<&Key>, Vec<&Key>) {
+        // count the consumers of each processor's outputs
+        let mut num_consumers: IndexMap<&Key, usize> =
+            self.processors.iter().map(|proc| (proc, 0)).collect();
+        let mut producers_of: HashMap<&Key, HashSet<&Key>> = HashMap::new();
+
+        for conn in self.adj_exchange.values() {
+            for producer in conn.sink_iter() {
+                for consumer in conn.source_iter() {
+                    if producer == consumer {
+                        continue;
+                    }
+                    let producers = producers_of.entry(consumer).or_default();
+                    if producers.insert(producer) {
+                        num_consumers[producer] += 1;
+                    }
+                }
+            }
+        }
+
+        // start from processors that no processors consume
+        let mut order = vec![];
+        let mut ready: Vec<&Key> = num_consumers
+            .iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(&proc, _)| proc)
+            .rev()
+            .collect();
+
+        while let Some(proc) = ready.pop() {
+            order.push(proc);
+            num_consumers.shift_remove(proc);
+
+            let mut producers: Vec<&Key> = producers_of
+                .get(proc)
+                .into_iter()
+                .flatten()
+                .copied()
+                .collect();
+            producers.sort_by_key(|producer| self.processors.get_index_of(*producer));
+
+            for producer in producers.into_iter().rev() {
+                if let Some(count) = num_consumers.get_mut(producer) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(producer);
+                    }
+                }
+            }
+        }
+
+        // the rest are in cycles
+        (order, num_consumers.into_keys().collect())
+    }
+
     /// Get the file bindings for each exchange namespace.
-    pub fn bindings(&self) -> &IndexMap<PathBuf, Dir> {
+    pub fn bindings(&self) -> &IndexMap<Dir, PathBuf> {
         &self.bindings
     }
 
+    /// Get the exchanges that the processor `proc` receives from, in
+    /// the order of connections.
+    pub fn inputs_of<N>(&self, proc: N) -> Result<Vec<&Key>, Error>
+    where
+        N: IntoKey,
+    {
+        let proc = proc.into();
+        let inputs = &self
+            .adj_processor
+            .get(&proc)
+            .ok_or_else(|| Error::processor_not_found(&proc))?
+            .inputs;
+        Ok(self.connected_exchanges(inputs.as_ref()))
+    }
+
+    /// Get the exchanges that the processor `proc` sends to, in the
+    /// order of connections.
+    pub fn outputs_of<N>(&self, proc: N) -> Result<Vec<&Key>, Error>
+    where
+        N: IntoKey,
+    {
+        let proc = proc.into();
+        let outputs = &self
+            .adj_processor
+            .get(&proc)
+            .ok_or_else(|| Error::processor_not_found(&proc))?
+            .outputs;
+        Ok(self.connected_exchanges(outputs.as_ref()))
+    }
+
+    fn connected_exchanges(&self, exchanges: Option<&HashSet<ARef<Key>>>) -> Vec<&Key> {
+        let Some(exchanges) = exchanges else {
+            return vec![];
+        };
+        self.adj_exchange
+            .keys()
+            .filter(|key| exchanges.contains(*key))
+            .collect()
+    }
+
+    /// Get the processors that send to the `exchange`.
+    pub fn producers_of<E>(&self, exchange: E) -> Result<Vec<&Key>, Error>
+    where
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        let conn = self.connection_of(&exchange)?;
+        Ok(conn.into_iter().flat_map(Connection::sink_iter).collect())
+    }
+
+    /// Get the processors that receive from the `exchange`.
+    pub fn consumers_of<E>(&self, exchange: E) -> Result<Vec<&Key>, Error>
+    where
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        let conn = self.connection_of(&exchange)?;
+        Ok(conn.into_iter().flat_map(Connection::source_iter).collect())
+    }
+
+    fn connection_of(&self, exchange: &Key) -> Result<Option<&Connection>, Error> {
+        if !self.exchanges.contains_key(exchange) {
+            return Err(Error::exchange_not_found(exchange));
+        }
+        Ok(self.adj_exchange.get(exchange))
+    }
+
+    /// Get the module that declares the `exchange`.
+    ///
+    /// The root dataflow is an empty [Dir]. The file of the module is
+    /// found in [bindings](Dataflow::bindings).
+    pub fn module_of_exchange<E>(&self, exchange: E) -> Result<Dir, Error>
+    where
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        if !self.exchanges.contains_key(&exchange) {
+            return Err(Error::exchange_not_found(&exchange));
+        }
+        Ok(exchange.parent())
+    }
+
+    /// Get the module that declares the processor `proc`.
+    ///
+    /// Processors in the flat namespace are not prefixed by module
+    /// names, so the module is tracked when the dataflow is
+    /// flattened. Dataflows opened from flattened files have every
+    /// processor in the root.
+    pub fn module_of_processor<N>(&self, proc: N) -> Result<Dir, Error>
+    where
+        N: IntoKey,
+    {
+        let proc = proc.into();
+        if !self.processors.contains(&proc) {
+            return Err(Error::processor_not_found(&proc));
+        }
+        let dir = self.graph.declarations.get(&proc).cloned();
+        Ok(dir.unwrap_or_else(|| Dir::new(vec![])))
+    }
+
+    /// Get all processors ordered so that every processor comes after
+    /// the processors it receives from.
+    ///
+    /// A processor that receives its own outputs is not regarded as a
+    /// cycle. It returns [Error::CyclicDataflow] if some processors
+    /// cannot be ordered due to cycles.
+    pub fn topological_order(&self) -> Result<Vec<&Key>, Error> {
+        let (mut order, cyclic) = self.consumer_first_order();
+        if !cyclic.is_empty() {
+            let processors = cyclic.into_iter().cloned().collect();
+            return Err(Error::CyclicDataflow { processors });
+        }
+        order.reverse();
+        Ok(order)
+    }
+
+    /// Build the receivers and senders of all exchanges connected to
+    /// the processor `proc`.
+    ///
+    /// The endpoints are built concurrently. It fails if any of them
+    /// fails to be built.
+    pub async fn build_processor<N>(&self, proc: N) -> Result<ProcessorIo, Error>
+    where
+        N: IntoKey,
+    {
+        let proc = proc.into();
+        let inputs = self.inputs_of(&proc)?;
+        let outputs = self.outputs_of(&proc)?;
+        let codecs = chain!(&inputs, &outputs)
+            .map(|&key| {
+                (
+                    key.clone(),
+                    self.exchanges[key]
+                        .message_config()
+                        .codec
+                        .unwrap_or_default(),
+                )
+            })
+            .collect();
+
+        let receivers = future::try_join_all(inputs.into_iter().map(|key| async move {
+            let receiver = self.exchanges[key].build_receiver().await?;
+            anyhow::Ok((key.clone(), receiver))
+        }));
+        let producer = proc.to_string();
+        let senders = future::try_join_all(outputs.into_iter().map(|key| {
+            let producer = producer.clone();
+            async move {
+                let mut sender = self.exchanges[key].build_sender().await?;
+                sender.set_producer(producer);
+                anyhow::Ok((key.clone(), sender))
+            }
+        }));
+        let (receivers, senders) = future::try_join(receivers, senders).await?;
+
+        Ok(ProcessorIo::new(
+            proc,
+            receivers.into_iter().collect(),
+            senders.into_iter().collect(),
+            codecs,
+        ))
+    }
+
     /// Build a receiver to an exhcnage for the single-input processor `proc`.
     ///
     /// The processor is implicitly assumed to connect to one input
@@ -147,7 +615,7 @@ impl Dataflow {
     /// [build_receiver_from](Graph::build_receiver_from) instead.
     pub async fn build_receiver<N>(&self, proc: N) -> Result<easyflow_link::Receiver, Error>
     where
-        N: IntoIdent,
+        N: IntoKey,
     {
         let proc = proc.into();
         let inputs = self
@@ -180,7 +648,7 @@ impl Dataflow {
     /// [build_sender_to](Graph::build_sender_to) instead.
     pub async fn build_sender<N>(&self, proc: N) -> Result<easyflow_link::Sender, Error>
     where
-        N: IntoIdent,
+        N: IntoKey,
     {
         let proc = proc.into();
         let outputs = self
@@ -203,7 +671,8 @@ impl Dataflow {
             exg
         };
 
-        let sender = self.exchanges[&**exchange].build_sender().await?;
+        let mut sender = self.exchanges[&**exchange].build_sender().await?;
+        sender.set_producer(proc.to_string());
 
         Ok(sender)
     }
@@ -215,7 +684,7 @@ impl Dataflow {
         exchange: E,
     ) -> Result<easyflow_link::Receiver, Error>
     where
-        N: IntoIdent,
+        N: IntoKey,
         E: IntoKey,
     {
         let proc = proc.into();
@@ -234,6 +703,54 @@ impl Dataflow {
         Ok(receiver)
     }
 
+    /// Build a receiver that yields messages from any of the
+    /// `exchanges` for the processor `proc` as they arrive, tagged
+    /// with the exchange keys.
+    pub async fn build_merged_receiver<N, I, E>(
+        &self,
+        proc: N,
+        exchanges: I,
+        policy: ClosePolicy,
+    ) -> Result<MergedReceiver, Error>
+    where
+        N: IntoKey,
+        I: IntoIterator<Item = E>,
+        E: IntoKey,
+    {
+        let proc = proc.into();
+        let exchanges: Vec<Key> = exchanges.into_iter().map(IntoKey::into).collect();
+        if exchanges.is_empty() {
+            return Err(Error::no_input_available(&proc));
+        }
+
+        let receivers = future::try_join_all(exchanges.into_iter().map(|exchange| {
+            let proc = &proc;
+            async move {
+                let receiver = self.build_receiver_from(proc, &exchange).await?;
+                Ok::<_, Error>((exchange, receiver))
+            }
+        }))
+        .await?;
+
+        Ok(MergedReceiver::new(receivers, policy))
+    }
+
+    /// Build a merged receiver from all input exchanges of the
+    /// processor `proc`. See
+    /// [build_merged_receiver](Dataflow::build_merged_receiver).
+    pub async fn build_merged_receiver_all<N>(
+        &self,
+        proc: N,
+        policy: ClosePolicy,
+    ) -> Result<MergedReceiver, Error>
+    where
+        N: IntoKey,
+    {
+        let proc = proc.into();
+        let inputs: Vec<Key> = self.inputs_of(&proc)?.into_iter().cloned().collect();
+        self.build_merged_receiver(proc, inputs, policy).await
+    }
+
     /// Build a sender to the `exhcnage` for the processor `proc`.
     pub async fn build_sender_to<N, E>(
         &self,
@@ -241,7 +758,7 @@ impl Dataflow {
         exchange: E,
     ) -> Result<easyflow_link::Sender, Error>
     where
-        N: IntoIdent,
+        N: IntoKey,
         E: IntoKey,
     {
         let proc = proc.into();
@@ -257,9 +774,163 @@ impl Dataflow {
         let key = outputs
             .get(&exchange)
             .ok_or_else(|| Error::connection_error(&proc, &exchange))?;
-        let sender = self.exchanges[&**key].build_sender().await?;
+        let mut sender = self.exchanges[&**key].build_sender().await?;
+        sender.set_producer(proc.to_string());
         Ok(sender)
     }
+
+    /// Build a sender that publishes each payload to all of the
+    /// `exchanges` concurrently for the processor `proc`.
+    pub async fn build_multi_sender<N, I, E>(
+        &self,
+        proc: N,
+        exchanges: I,
+        policy: FailurePolicy,
+    ) -> Result<MultiSender, Error>
+    where
+        N: IntoKey,
+        I: IntoIterator<Item = E>,
+        E: IntoKey,
+    {
+        let proc = proc.into();
+        let exchanges: Vec<Key> = exchanges.into_iter().map(IntoKey::into).collect();
+        if exchanges.is_empty() {
+            return Err(Error::no_output_available(&proc));
+        }
+
+        let senders = future::try_join_all(exchanges.into_iter().map(|exchange| {
+            let proc = &proc;
+            async move {
+                let sender = self.build_sender_to(proc, &exchange).await?;
+                Ok::<_, Error>((exchange, sender))
+            }
+        }))
+        .await?;
+
+        Ok(MultiSender::new(senders, policy))
+    }
+
+    /// Build a multi-sender to all output exchanges of the processor
+    /// `proc`. See [build_multi_sender](Dataflow::build_multi_sender).
+    pub async fn build_multi_sender_all<N>(
+        &self,
+        proc: N,
+        policy: FailurePolicy,
+    ) -> Result<MultiSender, Error>
+    where
+        N: IntoKey,
+    {
+        let proc = proc.into();
+        let outputs: Vec<Key> = self.outputs_of(&proc)?.into_iter().cloned().collect();
+        self.build_multi_sender(proc, outputs, policy).await
+    }
+
+    /// Build a receiver that decodes values of type `T` from an
+    /// exchange for the processor `proc`.
+    ///
+    /// Messages are decoded by the codec declared by the exchange.
+    pub async fn build_typed_receiver_from<T, N, E>(
+        &self,
+        proc: N,
+        exchange: E,
+    ) -> Result<TypedReceiver<T>, Error>
+    where
+        T: DeserializeOwned,
+        N: IntoKey,
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        let receiver = self.build_receiver_from(proc, &exchange).await?;
+        let codec = self.exchanges[&exchange]
+            .message_config()
+            .codec
+            .unwrap_or_default();
+        Ok(TypedReceiver::new(receiver, codec, exchange.to_string()))
+    }
+
+    /// Build a sender that encodes values of type `T` to an exchange
+    /// for the processor `proc`.
+    ///
+    /// Messages are encoded by the codec declared by the exchange.
+    pub async fn build_typed_sender_to<T, N, E>(
+        &self,
+        proc: N,
+        exchange: E,
+    ) -> Result<TypedSender<T>, Error>
+    where
+        T: Serialize,
+        N: IntoKey,
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        let sender = self.build_sender_to(proc, &exchange).await?;
+        let codec = self.exchanges[&exchange]
+            .message_config()
+            .codec
+            .unwrap_or_default();
+        Ok(TypedSender::new(sender, codec, exchange.to_string()))
+    }
+
+    /// Build a receiver of protobuf messages of type `T` from an
+    /// exchange for the processor `proc`.
+    ///
+    /// It fails if the exchange declares another message type.
+    #[cfg(feature = "protobuf")]
+    pub async fn build_protobuf_receiver_from<T, N, E>(
+        &self,
+        proc: N,
+        exchange: E,
+    ) -> Result<easyflow_link::protobuf::Receiver<T>, Error>
+    where
+        T: prost::Message + prost::Name + Default,
+        N: IntoKey,
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        self.check_message_type::<T>(&exchange)?;
+        let receiver = self.build_receiver_from(proc, &exchange).await?;
+        Ok(receiver.into_protobuf_decoded())
+    }
+
+    /// Build a sender of protobuf messages of type `T` to an exchange
+    /// for the processor `proc`.
+    ///
+    /// It fails if the exchange declares another message type.
+    #[cfg(feature = "protobuf")]
+    pub async fn build_protobuf_sender_to<T, N, E>(
+        &self,
+        proc: N,
+        exchange: E,
+    ) -> Result<easyflow_link::protobuf::Sender<T>, Error>
+    where
+        T: prost::Message + prost::Name,
+        N: IntoKey,
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        self.check_message_type::<T>(&exchange)?;
+        let sender = self.build_sender_to(proc, &exchange).await?;
+        Ok(sender.into_protobuf_encoded())
+    }
+
+    #[cfg(feature = "protobuf")]
+    fn check_message_type<T>(&self, exchange: &Key) -> Result<(), Error>
+    where
+        T: prost::Name,
+    {
+        use anyhow::Context as _;
+
+        let declared = self
+            .exchanges
+            .get(exchange)
+            .ok_or_else(|| Error::exchange_not_found(exchange))?
+            .message_config()
+            .message_type
+            .as_deref();
+        easyflow_link::protobuf::check_message_type::<T>(declared)
+            .with_context(|| format!("message type mismatch on exchange '{}'", exchange))?;
+        Ok(())
+    }
 }
 
 impl TryFrom<GraphConfig> for Dataflow {
@@ -276,50 +947,144 @@ impl From<Dataflow> for GraphConfig {
     }
 }
 
+/// Options to render a dataflow graph in the DOT language.
+#[derive(Debug, Clone)]
+pub struct DotOptions {
+    /// Draw only the processors and exchanges near this processor.
+    pub neighborhood: Option<Key>,
+    /// The number of exchanges to walk through from the
+    /// [neighborhood](DotOptions::neighborhood) processor. The default
+    /// 1 draws the exchanges that the processor is connected to, and
+    /// the processors on the other sides.
+    pub radius: usize,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        Self {
+            neighborhood: None,
+            radius: 1,
+        }
+    }
+}
+
+/// The nodes and edges of a dataflow graph.
+///
+/// It is serialized to JSON by
+/// [save_adjacency_file](Dataflow::save_adjacency_file).
+///
+/// ```json
+/// {
+///   "nodes": [
+///     { "kind": "processor", "key": "merger" },
+///     { "kind": "exchange", "key": "OUTPUT", "transport": "zenoh" }
+///   ],
+///   "edges": [
+///     { "processor": "merger", "exchange": "OUTPUT", "direction": "output" }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Adjacency {
+    pub nodes: Vec<AdjacencyNode>,
+    pub edges: Vec<AdjacencyEdge>,
+}
+
+/// A processor or an exchange in [Adjacency].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdjacencyNode {
+    pub kind: NodeKind,
+    pub key: Key,
+    /// The transport type of an exchange, such as `zenoh` or `file`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transport: Option<String>,
+}
+
+/// A connection between a processor and an exchange in [Adjacency].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdjacencyEdge {
+    pub processor: Key,
+    pub exchange: Key,
+    pub direction: EdgeDirection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Processor,
+    Exchange,
+}
+
+/// The direction of an edge seen from the processor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeDirection {
+    /// The processor receives from the exchange.
+    Input,
+    /// The processor sends to the exchange.
+    Output,
+}
+
 mod graphviz {
     use super::*;
-    use dot::{Edges, GraphWalk, Id, LabelText, Labeller, Nodes};
-    use std::borrow::Cow;
+    use dot::{Edges, GraphWalk, LabelText, Nodes};
+    use std::{borrow::Cow, fmt::Write};
 
-    #[derive(Clone)]
+    #[derive(Clone, PartialEq, Eq, Hash)]
     pub(crate) enum Node<'a> {
-        Processor(&'a Ident),
+        Processor(&'a Key),
         Exchange(&'a Key),
     }
 
     #[derive(Clone)]
     pub(crate) enum Edge<'a> {
-        SrcIn { src: &'a Key, input: &'a Ident },
-        SinkOut { sink: &'a Key, output: &'a Ident },
+        SrcIn { src: &'a Key, input: &'a Key },
+        SinkOut { sink: &'a Key, output: &'a Key },
     }
 
-    impl<'a> Labeller<'a, Node<'a>, Edge<'a>> for Dataflow {
-        fn graph_id(&'a self) -> Id<'a> {
-            Id::new("flow_graph").unwrap()
+    /// The DOT id of a node. Keys may collide once escaped and may
+    /// start with a digit, so nodes are numbered by their index in
+    /// the processors or exchanges instead. The keys are kept in the
+    /// labels.
+    fn node_id(dataflow: &Dataflow, node: &Node) -> String {
+        match node {
+            Node::Processor(proc) => {
+                format!("p{}", dataflow.processors.get_index_of(*proc).unwrap())
+            }
+            Node::Exchange(exg) => format!("e{}", dataflow.exchanges.get_index_of(*exg).unwrap()),
         }
+    }
 
-        fn node_id(&'a self, node: &Node<'a>) -> Id<'a> {
-            let name = match node {
-                Node::Processor(proc) => proc.to_string(),
-                Node::Exchange(exg) => exg.to_string(),
-            };
-            Id::new(name.replace(&['-', '/'][..], "_")).unwrap()
-        }
+    fn node_label(dataflow: &Dataflow, node: &Node) -> LabelText<'static> {
+        let label = match node {
+            Node::Processor(proc) => proc.to_string(),
+            Node::Exchange(exg) => {
+                let exchange = &dataflow.exchanges[*exg];
+                match exchange.endpoint() {
+                    Some(endpoint) => {
+                        format!("{}\n{}: {}", exg, exchange.type_name(), endpoint)
+                    }
+                    None => format!("{}\n{}", exg, exchange.type_name()),
+                }
+            }
+        };
+        LabelText::LabelStr(Into::into(label))
+    }
 
-        fn node_label(&'a self, node: &Node<'a>) -> LabelText<'a> {
-            let name = match node {
-                Node::Processor(proc) => proc.to_string(),
-                Node::Exchange(exg) => exg.to_string(),
-            };
-            LabelText::LabelStr(Into::into(name))
+    fn node_color(dataflow: &Dataflow, node: &Node) -> Option<LabelText<'static>> {
+        match node {
+            Node::Processor(_) => None,
+            Node::Exchange(exg) => {
+                let color = transport_color(&dataflow.exchanges[*exg]);
+                Some(LabelText::LabelStr(Into::into(color)))
+            }
         }
+    }
 
-        fn node_shape(&'a self, node: &Node<'a>) -> Option<LabelText<'a>> {
-            let shape = match node {
-                Node::Processor(_) => LabelText::LabelStr(Into::into("box")),
-                Node::Exchange(_) => LabelText::LabelStr(Into::into("hexagon")),
-            };
-            Some(shape)
+    fn node_shape(node: &Node) -> LabelText<'static> {
+        match node {
+            Node::Processor(_) => LabelText::LabelStr(Into::into("box")),
+            Node::Exchange(_) => LabelText::LabelStr(Into::into("hexagon")),
         }
     }
 
@@ -371,4 +1136,212 @@ mod graphviz {
             }
         }
     }
+
+    /// Render the graph in DOT, including only the `only` nodes if given.
+    ///
+    /// The dot crate does not support subgraphs, so the output is
+    /// written here, escaping labels with [LabelText].
+    pub(crate) fn render_dot(dataflow: &Dataflow, only: Option<&HashSet<Node<'_>>>) -> String {
+        let included = |node: &Node| only.is_none_or(|only| only.contains(node));
+
+        // group nodes by the modules declaring them
+        let mut members: HashMap<Dir, Vec<Node>> = HashMap::new();
+        let mut dirs: IndexSet<Dir> = IndexSet::new();
+        for node in dataflow.nodes().iter().filter(|node| included(node)) {
+            let dir = match node {
+                Node::Processor(proc) => dataflow.graph.declarations.get(*proc).cloned(),
+                Node::Exchange(exg) => Some(exg.parent()),
+            };
+            let dir = dir.unwrap_or_else(|| Dir::new(vec![]));
+
+            // parent modules are drawn even if they declare nothing
+            let mut ancestor = Some(dir.clone());
+            while let Some(dir) = ancestor {
+                ancestor = dir.parent();
+                dirs.insert(dir);
+            }
+            members.entry(dir).or_default().push(node.clone());
+        }
+
+        let mut out = String::new();
+        out.push_str("digraph flow_graph {\n");
+        write_cluster(dataflow, &Dir::new(vec![]), &members, &dirs, 1, &mut out);
+
+        for edge in dataflow.edges().iter() {
+            let source = dataflow.source(edge);
+            let target = dataflow.target(edge);
+            if included(&source) && included(&target) {
+                writeln!(
+                    out,
+                    "    {} -> {};",
+                    node_id(dataflow, &source),
+                    node_id(dataflow, &target)
+                )
+                .unwrap();
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Write the nodes in the module `dir` and the clusters of its
+    /// submodules.
+    fn write_cluster(
+        dataflow: &Dataflow,
+        dir: &Dir,
+        members: &HashMap<Dir, Vec<Node>>,
+        dirs: &IndexSet<Dir>,
+        depth: usize,
+        out: &mut String,
+    ) {
+        let indent = "    ".repeat(depth);
+
+        for node in members.get(dir).into_iter().flatten() {
+            write!(
+                out,
+                "{}{}[label={}]",
+                indent,
+                node_id(dataflow, node),
+                node_label(dataflow, node).to_dot_string()
+            )
+            .unwrap();
+            write!(out, "[shape={}]", node_shape(node).to_dot_string()).unwrap();
+            if let Some(color) = node_color(dataflow, node) {
+                write!(out, "[color={}]", color.to_dot_string()).unwrap();
+            }
+            out.push_str(";\n");
+        }
+
+        for child in dirs
+            .iter()
+            .filter(|child| child.parent().as_ref() == Some(dir))
+        {
+            let label = LabelText::LabelStr(Into::into(child.to_string()));
+            // module names may collide once escaped, so clusters are
+            // numbered as well
+            let index = dirs.get_index_of(child).unwrap();
+            writeln!(out, "{}subgraph cluster_{} {{", indent, index).unwrap();
+            writeln!(out, "{}    label={};", indent, label.to_dot_string()).unwrap();
+            write_cluster(dataflow, child, members, dirs, depth + 1, out);
+            writeln!(out, "{}}}", indent).unwrap();
+        }
+    }
+
+    /// Collect the processors and exchanges within `radius` exchanges
+    /// from the processor `proc`.
+    pub(crate) fn neighborhood<'a>(
+        dataflow: &'a Dataflow,
+        proc: &Key,
+        radius: usize,
+    ) -> Result<HashSet<Node<'a>>, Error> {
+        let proc = dataflow
+            .processors
+            .get(proc)
+            .ok_or_else(|| Error::processor_not_found(proc))?;
+        let mut nodes = HashSet::from([Node::Processor(proc)]);
+        let mut frontier = vec![proc];
+
+        for _ in 0..radius {
+            let mut next = vec![];
+
+            for (exchange, conn) in dataflow.adj_exchange.iter() {
+                let procs: Vec<&Key> = chain!(conn.sink_iter(), conn.source_iter()).collect();
+                if !procs.iter().any(|proc| frontier.contains(proc)) {
+                    continue;
+                }
+
+                nodes.insert(Node::Exchange(exchange));
+                for proc in procs {
+                    if nodes.insert(Node::Processor(proc)) {
+                        next.push(proc);
+                    }
+                }
+            }
+
+            frontier = next;
+        }
+
+        Ok(nodes)
+    }
+
+    fn transport_color(exchange: &Exchange) -> &'static str {
+        match exchange.type_name() {
+            "file" => "darkgoldenrod",
+            "zenoh" => "royalblue",
+            "amqp" => "darkorange",
+            "unix" => "forestgreen",
+            _ => "gray",
+        }
+    }
+
+    pub(crate) fn render_mermaid(dataflow: &Dataflow) -> String {
+        let nodes = dataflow.nodes();
+        let mut out = String::from("flowchart LR\n");
+
+        // Keys are not valid Mermaid ids, so nodes are numbered instead.
+        let mut ids = HashMap::new();
+        for (index, node) in nodes.iter().enumerate() {
+            let (id, label) = match node {
+                Node::Processor(key) => (format!("p{}", index), format!("[\"{}\"]", escape(key))),
+                Node::Exchange(key) => (
+                    format!("e{}", index),
+                    format!("{{{{\"{}\"}}}}", escape(key)),
+                ),
+            };
+            writeln!(out, "    {}{}", id, label).unwrap();
+            ids.insert(node, id);
+        }
+
+        for edge in dataflow.edges().iter() {
+            let source = &ids[&dataflow.source(edge)];
+            let target = &ids[&dataflow.target(edge)];
+            writeln!(out, "    {} --> {}", source, target).unwrap();
+        }
+
+        out
+    }
+
+    pub(crate) fn adjacency(dataflow: &Dataflow) -> Adjacency {
+        let nodes = dataflow
+            .nodes()
+            .iter()
+            .map(|node| match *node {
+                Node::Processor(key) => AdjacencyNode {
+                    kind: NodeKind::Processor,
+                    key: key.clone(),
+                    transport: None,
+                },
+                Node::Exchange(key) => AdjacencyNode {
+                    kind: NodeKind::Exchange,
+                    key: key.clone(),
+                    transport: Some(dataflow.exchanges[key].type_name().to_string()),
+                },
+            })
+            .collect();
+
+        let edges = dataflow
+            .edges()
+            .iter()
+            .map(|edge| match *edge {
+                Edge::SrcIn { src, input } => AdjacencyEdge {
+                    processor: input.clone(),
+                    exchange: src.clone(),
+                    direction: EdgeDirection::Input,
+                },
+                Edge::SinkOut { sink, output } => AdjacencyEdge {
+                    processor: output.clone(),
+                    exchange: sink.clone(),
+                    direction: EdgeDirection::Output,
+                },
+            })
+            .collect();
+
+        Adjacency { nodes, edges }
+    }
+
+    /// Escape double quotes in a Mermaid label.
+    fn escape(key: &Key) -> String {
+        key.to_string().replace('"', "#quot;")
+    }
 }
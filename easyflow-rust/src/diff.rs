@@ -0,0 +1,244 @@
+use crate::Dataflow;
+use easyflow_config::{Connection, Key};
+use easyflow_link::Config as Exchange;
+use indexmap::{IndexMap, IndexSet};
+use serde_json::Value;
+use std::fmt::{self, Display};
+
+/// The semantic difference between two flattened dataflows.
+///
+/// Processors and exchanges are compared by their full keys, so
+/// moving a declaration between modules without changing the key is
+/// not a difference.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataflowDiff {
+    pub added_processors: Vec<Key>,
+    pub removed_processors: Vec<Key>,
+    pub added_exchanges: Vec<Key>,
+    pub removed_exchanges: Vec<Key>,
+    /// Exchanges declared in both dataflows with changed configurations.
+    pub changed_exchanges: IndexMap<Key, Vec<FieldChange>>,
+    /// Exchanges whose connected processors are changed.
+    pub changed_connections: IndexMap<Key, ConnectionChange>,
+}
+
+/// A changed field in an exchange configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    /// The dot-separated path to the field, such as `type` or `key`.
+    pub field: String,
+    /// The old value, or `None` if the field is added.
+    pub old: Option<Value>,
+    /// The new value, or `None` if the field is removed.
+    pub new: Option<Value>,
+}
+
+/// The changes of processors connected to an exchange.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionChange {
+    pub added_sinks: Vec<Key>,
+    pub removed_sinks: Vec<Key>,
+    pub added_sources: Vec<Key>,
+    pub removed_sources: Vec<Key>,
+}
+
+impl DataflowDiff {
+    /// Compare the `old` dataflow to the `new` one.
+    pub fn new(old: &Dataflow, new: &Dataflow) -> Self {
+        let (added_processors, removed_processors) =
+            set_diff(old.processors().iter(), new.processors().iter());
+        let (added_exchanges, removed_exchanges) =
+            set_diff(old.exchanges().keys(), new.exchanges().keys());
+
+        let changed_exchanges = old
+            .exchanges()
+            .iter()
+            .filter_map(|(key, old_ex)| {
+                let new_ex = new.exchanges().get(key)?;
+                let changes = exchange_diff(old_ex, new_ex);
+                (!changes.is_empty()).then(|| (key.clone(), changes))
+            })
+            .collect();
+
+        let conn_keys: IndexSet<&Key> = old
+            .connections()
+            .keys()
+            .chain(new.connections().keys())
+            .collect();
+        let changed_connections = conn_keys
+            .into_iter()
+            .filter_map(|key| {
+                let change =
+                    ConnectionChange::new(old.connections().get(key), new.connections().get(key));
+                (!change.is_empty()).then(|| (key.clone(), change))
+            })
+            .collect();
+
+        Self {
+            added_processors,
+            removed_processors,
+            added_exchanges,
+            removed_exchanges,
+            changed_exchanges,
+            changed_connections,
+        }
+    }
+
+    /// Return true if the dataflows are identical.
+    pub fn is_empty(&self) -> bool {
+        self.added_processors.is_empty()
+            && self.removed_processors.is_empty()
+            && self.added_exchanges.is_empty()
+            && self.removed_exchanges.is_empty()
+            && self.changed_exchanges.is_empty()
+            && self.changed_connections.is_empty()
+    }
+}
+
+impl Display for DataflowDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for key in &self.added_processors {
+            writeln!(f, "+ processor {}", key)?;
+        }
+        for key in &self.removed_processors {
+            writeln!(f, "- processor {}", key)?;
+        }
+        for key in &self.added_exchanges {
+            writeln!(f, "+ exchange {}", key)?;
+        }
+        for key in &self.removed_exchanges {
+            writeln!(f, "- exchange {}", key)?;
+        }
+
+        for (key, changes) in &self.changed_exchanges {
+            writeln!(f, "~ exchange {}", key)?;
+            for change in changes {
+                writeln!(f, "    {}", change)?;
+            }
+        }
+
+        for (key, change) in &self.changed_connections {
+            writeln!(f, "~ connection {}", key)?;
+            for proc in &change.added_sinks {
+                writeln!(f, "    + < {}", proc)?;
+            }
+            for proc in &change.removed_sinks {
+                writeln!(f, "    - < {}", proc)?;
+            }
+            for proc in &change.added_sources {
+                writeln!(f, "    + > {}", proc)?;
+            }
+            for proc in &change.removed_sources {
+                writeln!(f, "    - > {}", proc)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |value: &Option<Value>| match value {
+            Some(value) => value.to_string(),
+            None => "(none)".to_string(),
+        };
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.field,
+            show(&self.old),
+            show(&self.new)
+        )
+    }
+}
+
+impl ConnectionChange {
+    fn new(old: Option<&Connection>, new: Option<&Connection>) -> Self {
+        let (added_sinks, removed_sinks) = set_diff(
+            old.into_iter().flat_map(Connection::sink_iter),
+            new.into_iter().flat_map(Connection::sink_iter),
+        );
+        let (added_sources, removed_sources) = set_diff(
+            old.into_iter().flat_map(Connection::source_iter),
+            new.into_iter().flat_map(Connection::source_iter),
+        );
+
+        Self {
+            added_sinks,
+            removed_sinks,
+            added_sources,
+            removed_sources,
+        }
+    }
+
+    /// Return true if the connected processors are not changed.
+    pub fn is_empty(&self) -> bool {
+        self.added_sinks.is_empty()
+            && self.removed_sinks.is_empty()
+            && self.added_sources.is_empty()
+            && self.removed_sources.is_empty()
+    }
+}
+
+/// List the keys added to and removed from the old set.
+fn set_diff<'a, O, N>(old: O, new: N) -> (Vec<Key>, Vec<Key>)
+where
+    O: IntoIterator<Item = &'a Key>,
+    N: IntoIterator<Item = &'a Key>,
+{
+    let old: IndexSet<&Key> = old.into_iter().collect();
+    let new: IndexSet<&Key> = new.into_iter().collect();
+    let added = new.difference(&old).map(|&key| key.clone()).collect();
+    let removed = old.difference(&new).map(|&key| key.clone()).collect();
+    (added, removed)
+}
+
+/// Compare exchange configurations field by field.
+fn exchange_diff(old: &Exchange, new: &Exchange) -> Vec<FieldChange> {
+    let old = serde_json::to_value(old).unwrap();
+    let new = serde_json::to_value(new).unwrap();
+    let mut changes = vec![];
+    value_diff("", &old, &new, &mut changes);
+    changes
+}
+
+fn value_diff(path: &str, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+    let join = |name: &str| {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", path, name)
+        }
+    };
+
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (name, old_value) in old {
+                match new.get(name) {
+                    Some(new_value) => value_diff(&join(name), old_value, new_value, changes),
+                    None => changes.push(FieldChange {
+                        field: join(name),
+                        old: Some(old_value.clone()),
+                        new: None,
+                    }),
+                }
+            }
+            for (name, new_value) in new {
+                if !old.contains_key(name) {
+                    changes.push(FieldChange {
+                        field: join(name),
+                        old: None,
+                        new: Some(new_value.clone()),
+                    });
+                }
+            }
+        }
+        (old, new) if old != new => changes.push(FieldChange {
+            field: path.to_string(),
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
@@ -1,22 +1,27 @@
-use easyflow_config::{Ident, IntoIdent, IntoKey, Key};
+use easyflow_config::{IntoKey, Key};
+use itertools::Itertools as _;
 use std::io;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("processor `{processor}` not found")]
-    ProcessorNotFound { processor: Ident },
+    ProcessorNotFound { processor: Key },
     #[error("exchange `{exchange}` not found")]
     ExchangeNotFound { exchange: Key },
     #[error("processor `{processor}` has no inputs")]
-    NoInputAvailable { processor: Ident },
+    NoInputAvailable { processor: Key },
     #[error("processor `{processor}` has no outputs")]
-    NoOutputAvailable { processor: Ident },
+    NoOutputAvailable { processor: Key },
     #[error("the exchange `{exchange}` is not connected to the processor `{processor}`")]
-    ConnectionError { processor: Ident, exchange: Key },
+    ConnectionError { processor: Key, exchange: Key },
+    #[error("the endpoint of processor `{processor}` to exchange `{exchange}` is already taken")]
+    EndpointTaken { processor: Key, exchange: Key },
     #[error("the input exchange to processor `{processor}` must be specified")]
-    InputNotSpecified { processor: Ident },
+    InputNotSpecified { processor: Key },
     #[error("the output exchange from processor `{processor}` must be specified")]
-    OutputNotSpecified { processor: Ident },
+    OutputNotSpecified { processor: Key },
+    #[error("the dataflow contains a cycle involving processors {}", .processors.iter().map(|proc| format!("`{}`", proc)).join(", "))]
+    CyclicDataflow { processors: Vec<Key> },
     #[error("I/O error: {0}")]
     Io(#[from] io::Error),
     #[error("internal error: {0}")]
@@ -24,7 +29,7 @@ pub enum Error {
 }
 
 impl Error {
-    pub fn processor_not_found(processor: impl IntoIdent) -> Self {
+    pub fn processor_not_found(processor: impl IntoKey) -> Self {
         Self::ProcessorNotFound {
             processor: processor.into(),
         }
@@ -35,34 +40,41 @@ impl Error {
             exchange: exchange.into(),
         }
     }
-    pub fn no_input_available(processor: impl IntoIdent) -> Self {
+    pub fn no_input_available(processor: impl IntoKey) -> Self {
         Self::NoInputAvailable {
             processor: processor.into(),
         }
     }
 
-    pub fn no_output_available(processor: impl IntoIdent) -> Self {
+    pub fn no_output_available(processor: impl IntoKey) -> Self {
         Self::NoOutputAvailable {
             processor: processor.into(),
         }
     }
 
-    pub fn input_not_specified(processor: impl IntoIdent) -> Self {
+    pub fn input_not_specified(processor: impl IntoKey) -> Self {
         Self::InputNotSpecified {
             processor: processor.into(),
         }
     }
 
-    pub fn output_not_specified(processor: impl IntoIdent) -> Self {
+    pub fn output_not_specified(processor: impl IntoKey) -> Self {
         Self::OutputNotSpecified {
             processor: processor.into(),
         }
     }
 
-    pub fn connection_error(processor: impl IntoIdent, exchange: impl IntoKey) -> Self {
+    pub fn connection_error(processor: impl IntoKey, exchange: impl IntoKey) -> Self {
         Self::ConnectionError {
             processor: processor.into(),
             exchange: exchange.into(),
         }
     }
+
+    pub fn endpoint_taken(processor: impl IntoKey, exchange: impl IntoKey) -> Self {
+        Self::EndpointTaken {
+            processor: processor.into(),
+            exchange: exchange.into(),
+        }
+    }
 }
@@ -9,7 +9,8 @@
 //! # Dataflow Configuration Format
 //!
 //! The dataflow configuratoin file is written in JSON5 format,
-//! declaring the following primitives:
+//! declaring the following primitives. TOML and YAML are accepted as
+//! well for files with `.toml`, `.yaml` or `.yml` extensions.
 //!
 //! - **Processor**: The program with zero, one or more inputs and outputs.
 //! - **Exchange**: It connects the inputs of one or more processors to the outputs of the other processors.
@@ -17,7 +18,7 @@
 //! ```json5
 //! {
 //!     // The version number is mandatory.
-//!     "version": "0.1.0",
+//!     "version": "0.2.0",
 //!
 //!     // The list of processor names.
 //!     "processors": [
@@ -80,7 +81,18 @@
 //! ```
 
 mod dataflow;
+mod diff;
 mod error;
+mod merged;
+mod multi_sender;
+mod processor;
+mod synchronizer;
 
 pub use dataflow::*;
+pub use diff::*;
+pub use easyflow_link::{Codec, Message, Meta, TypedReceiver, TypedSender};
 pub use error::Error;
+pub use merged::*;
+pub use multi_sender::*;
+pub use processor::*;
+pub use synchronizer::*;
@@ -0,0 +1,121 @@
+use anyhow::{Context as _, Result};
+use derivative::Derivative;
+use easyflow_config::Key;
+use easyflow_link::{Message, Receiver};
+use futures::{
+    future,
+    stream::{self, BoxStream, SelectAll, Stream, StreamExt as _},
+};
+use indexmap::IndexSet;
+
+/// What a [MergedReceiver] does when one of its inputs is closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClosePolicy {
+    /// Stop receiving as soon as any input is closed.
+    #[default]
+    Stop,
+    /// Keep receiving from other inputs until all inputs are closed.
+    Continue,
+}
+
+/// A receiver that yields messages from several exchanges as they
+/// arrive, tagged with their exchange keys.
+///
+/// Inputs are polled fairly, so that a busy exchange does not starve
+/// the others. It is built by
+/// [build_merged_receiver](crate::Dataflow::build_merged_receiver).
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct MergedReceiver {
+    #[derivative(Debug = "ignore")]
+    inputs: SelectAll<BoxStream<'static, (Key, Option<Result<Message>>)>>,
+    exchanges: Vec<Key>,
+    closed: IndexSet<Key>,
+    policy: ClosePolicy,
+    finished: bool,
+}
+
+impl MergedReceiver {
+    /// Merge receivers by their exchange keys.
+    pub fn new(receivers: impl IntoIterator<Item = (Key, Receiver)>, policy: ClosePolicy) -> Self {
+        let mut exchanges = vec![];
+        let inputs: SelectAll<_> = receivers
+            .into_iter()
+            .map(|(key, receiver)| {
+                exchanges.push(key.clone());
+
+                // Mark the end of each input with `None`.
+                receiver
+                    .into_message_stream()
+                    .map(Some)
+                    .chain(stream::once(future::ready(None)))
+                    .map(move |item| (key.clone(), item))
+                    .boxed()
+            })
+            .collect();
+
+        Self {
+            finished: inputs.is_empty(),
+            inputs,
+            exchanges,
+            closed: IndexSet::new(),
+            policy,
+        }
+    }
+
+    /// Receive the next payload along with the exchange it comes
+    /// from. It returns `None` when the receiver stops according to
+    /// the [ClosePolicy].
+    pub async fn recv(&mut self) -> Result<Option<(Key, Vec<u8>)>> {
+        let item = self.recv_message().await?;
+        Ok(item.map(|(key, message)| (key, message.payload)))
+    }
+
+    /// Receive the next message along with the exchange it comes
+    /// from.
+    pub async fn recv_message(&mut self) -> Result<Option<(Key, Message)>> {
+        while !self.finished {
+            let Some((key, item)) = self.inputs.next().await else {
+                self.finished = true;
+                break;
+            };
+
+            match item {
+                Some(message) => {
+                    let message = message
+                        .with_context(|| format!("unable to receive from exchange '{key}'"))?;
+                    return Ok(Some((key, message)));
+                }
+                None => {
+                    self.closed.insert(key);
+                    if self.policy == ClosePolicy::Stop {
+                        self.finished = true;
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The merged exchanges in the order they are given.
+    pub fn exchanges(&self) -> &[Key] {
+        &self.exchanges
+    }
+
+    /// The exchanges closed so far, in the order they are closed.
+    pub fn closed(&self) -> impl Iterator<Item = &Key> {
+        self.closed.iter()
+    }
+
+    pub fn is_closed(&self, exchange: &Key) -> bool {
+        self.closed.contains(exchange)
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = Result<(Key, Vec<u8>)>> {
+        stream::try_unfold(self, |mut rx| async move {
+            let item = rx.recv().await?;
+            anyhow::Ok(item.map(|item| (item, rx)))
+        })
+    }
+}
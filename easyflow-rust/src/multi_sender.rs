@@ -0,0 +1,160 @@
+use anyhow::{bail, Context as _, Error, Result};
+use easyflow_config::Key;
+use easyflow_link::Sender;
+use futures::{
+    future,
+    sink::{self, Sink},
+};
+use itertools::Itertools as _;
+use std::{borrow::Cow, collections::BTreeMap};
+
+/// What a [MultiSender] does when some of the exchanges fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FailurePolicy {
+    /// Fail if any exchange fails. The error is returned only after
+    /// the sends to all exchanges complete, so that no message is
+    /// left half-written.
+    #[default]
+    AllOrError,
+    /// Send to all exchanges, and fail only if all of them fail. The
+    /// returned error lists the errors of all exchanges. Use
+    /// [Report](FailurePolicy::Report) to inspect partial failures.
+    BestEffort,
+    /// Send to all exchanges and never fail. Errors are reported per
+    /// exchange in the [SendReport].
+    Report,
+}
+
+/// The results of sending one payload to several exchanges, in the
+/// order of exchanges.
+#[derive(Debug, Default)]
+pub struct SendReport {
+    pub results: Vec<(Key, Result<()>)>,
+}
+
+impl SendReport {
+    /// Check if the payload is sent to all exchanges.
+    pub fn is_ok(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_ok())
+    }
+
+    /// The exchanges that the payload is sent to.
+    pub fn succeeded(&self) -> impl Iterator<Item = &Key> {
+        self.results
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .map(|(key, _)| key)
+    }
+
+    /// The exchanges that fail along with the errors.
+    pub fn failed(&self) -> impl Iterator<Item = (&Key, &Error)> {
+        self.results
+            .iter()
+            .filter_map(|(key, result)| Some((key, result.as_ref().err()?)))
+    }
+}
+
+/// A sender that publishes each payload to several exchanges
+/// concurrently.
+///
+/// It is built by
+/// [build_multi_sender](crate::Dataflow::build_multi_sender).
+/// Partial failures are handled according to the [FailurePolicy].
+#[derive(Debug)]
+pub struct MultiSender {
+    senders: Vec<(Key, Sender)>,
+    policy: FailurePolicy,
+}
+
+impl MultiSender {
+    /// Combine senders by their exchange keys.
+    pub fn new(senders: impl IntoIterator<Item = (Key, Sender)>, policy: FailurePolicy) -> Self {
+        Self {
+            senders: senders.into_iter().collect(),
+            policy,
+        }
+    }
+
+    /// The exchanges to send to, in the order they are given.
+    pub fn exchanges(&self) -> impl Iterator<Item = &Key> {
+        self.senders.iter().map(|(key, _)| key)
+    }
+
+    pub fn policy(&self) -> FailurePolicy {
+        self.policy
+    }
+
+    pub async fn send(&mut self, payload: impl Into<Cow<'_, [u8]>>) -> Result<SendReport> {
+        self.send_with_headers(payload, BTreeMap::new()).await
+    }
+
+    /// Send a payload with user headers to all exchanges. See
+    /// [Sender::send_with_headers].
+    pub async fn send_with_headers(
+        &mut self,
+        payload: impl Into<Cow<'_, [u8]>>,
+        headers: BTreeMap<String, String>,
+    ) -> Result<SendReport> {
+        let payload = payload.into();
+        let payload: &[u8] = &payload;
+
+        let sends = self.senders.iter_mut().map(|(key, sender)| {
+            let headers = headers.clone();
+            async move {
+                let result = sender
+                    .send_with_headers(payload, headers)
+                    .await
+                    .with_context(|| format!("unable to send to exchange '{key}'"));
+                (&*key, result)
+            }
+        });
+
+        // Sends are never cancelled halfway. A cancelled send may
+        // leave a partial message on the transport.
+        let report = collect_report(future::join_all(sends).await);
+
+        match self.policy {
+            FailurePolicy::AllOrError => {
+                if !report.is_ok() {
+                    let err = report
+                        .results
+                        .into_iter()
+                        .find_map(|(_, result)| result.err())
+                        .unwrap();
+                    return Err(err);
+                }
+                Ok(report)
+            }
+            FailurePolicy::BestEffort => {
+                if !report.results.is_empty() && report.succeeded().next().is_none() {
+                    let errors = report
+                        .failed()
+                        .map(|(_, err)| format!("{err:#}"))
+                        .join("; ");
+                    bail!("unable to send to any exchange: {errors}");
+                }
+                Ok(report)
+            }
+            FailurePolicy::Report => Ok(report),
+        }
+    }
+
+    /// Get the underlying senders by exchange keys.
+    pub fn into_inner(self) -> Vec<(Key, Sender)> {
+        self.senders
+    }
+
+    pub fn into_sink(self) -> impl Sink<Vec<u8>, Error = Error> {
+        sink::unfold(self, |mut sender, payload: Vec<u8>| async move {
+            sender.send(payload).await.map(|_| sender)
+        })
+    }
+}
+
+fn collect_report(results: Vec<(&Key, Result<()>)>) -> SendReport {
+    let results = results
+        .into_iter()
+        .map(|(key, result)| (key.clone(), result))
+        .collect();
+    SendReport { results }
+}
@@ -0,0 +1,169 @@
+use crate::error::Error;
+use easyflow_config::{IntoKey, Key};
+use easyflow_link::{Codec, Receiver, Sender, TypedReceiver, TypedSender};
+use indexmap::IndexMap;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The receivers and senders of all exchanges connected to a
+/// processor.
+///
+/// It is built by [build_processor](crate::Dataflow::build_processor).
+/// Endpoints are looked up by exchange keys. Use
+/// [take_receiver](ProcessorIo::take_receiver) and
+/// [take_sender](ProcessorIo::take_sender) to move endpoints out, for
+/// example to receive from several exchanges concurrently.
+#[derive(Debug)]
+pub struct ProcessorIo {
+    processor: Key,
+    /// Receivers by input exchanges. Taken ones are `None`.
+    receivers: IndexMap<Key, Option<Receiver>>,
+    /// Senders by output exchanges. Taken ones are `None`.
+    senders: IndexMap<Key, Option<Sender>>,
+    /// Codecs declared by connected exchanges.
+    codecs: IndexMap<Key, Codec>,
+}
+
+impl ProcessorIo {
+    pub(crate) fn new(
+        processor: Key,
+        receivers: IndexMap<Key, Receiver>,
+        senders: IndexMap<Key, Sender>,
+        codecs: IndexMap<Key, Codec>,
+    ) -> Self {
+        Self {
+            processor,
+            receivers: receivers
+                .into_iter()
+                .map(|(key, rx)| (key, Some(rx)))
+                .collect(),
+            senders: senders
+                .into_iter()
+                .map(|(key, tx)| (key, Some(tx)))
+                .collect(),
+            codecs,
+        }
+    }
+
+    /// The processor name.
+    pub fn processor(&self) -> &Key {
+        &self.processor
+    }
+
+    /// Get the input exchange keys, including the taken ones.
+    pub fn inputs(&self) -> impl Iterator<Item = &Key> {
+        self.receivers.keys()
+    }
+
+    /// Get the output exchange keys, including the taken ones.
+    pub fn outputs(&self) -> impl Iterator<Item = &Key> {
+        self.senders.keys()
+    }
+
+    /// Get the codec declared by the connected `exchange`.
+    pub fn codec<E>(&self, exchange: E) -> Result<Codec, Error>
+    where
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        self.codecs
+            .get(&exchange)
+            .copied()
+            .ok_or_else(|| Error::connection_error(&self.processor, &exchange))
+    }
+
+    /// Get the receiver from the `exchange`.
+    pub fn receiver<E>(&mut self, exchange: E) -> Result<&mut Receiver, Error>
+    where
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        let rx = endpoint(&self.processor, &mut self.receivers, exchange)?
+            .as_mut()
+            .unwrap();
+        Ok(rx)
+    }
+
+    /// Get the sender to the `exchange`.
+    pub fn sender<E>(&mut self, exchange: E) -> Result<&mut Sender, Error>
+    where
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        let tx = endpoint(&self.processor, &mut self.senders, exchange)?
+            .as_mut()
+            .unwrap();
+        Ok(tx)
+    }
+
+    /// Move the receiver from the `exchange` out.
+    pub fn take_receiver<E>(&mut self, exchange: E) -> Result<Receiver, Error>
+    where
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        let rx = endpoint(&self.processor, &mut self.receivers, exchange)?
+            .take()
+            .unwrap();
+        Ok(rx)
+    }
+
+    /// Move the sender to the `exchange` out.
+    pub fn take_sender<E>(&mut self, exchange: E) -> Result<Sender, Error>
+    where
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        let tx = endpoint(&self.processor, &mut self.senders, exchange)?
+            .take()
+            .unwrap();
+        Ok(tx)
+    }
+
+    /// Move the receiver from the `exchange` out, decoding values of
+    /// type `T` by the codec of the exchange.
+    pub fn take_typed_receiver<T, E>(&mut self, exchange: E) -> Result<TypedReceiver<T>, Error>
+    where
+        T: DeserializeOwned,
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        let rx = self.take_receiver(&exchange)?;
+        Ok(TypedReceiver::new(
+            rx,
+            self.codecs[&exchange],
+            exchange.to_string(),
+        ))
+    }
+
+    /// Move the sender to the `exchange` out, encoding values of type
+    /// `T` by the codec of the exchange.
+    pub fn take_typed_sender<T, E>(&mut self, exchange: E) -> Result<TypedSender<T>, Error>
+    where
+        T: Serialize,
+        E: IntoKey,
+    {
+        let exchange = exchange.into();
+        let tx = self.take_sender(&exchange)?;
+        Ok(TypedSender::new(
+            tx,
+            self.codecs[&exchange],
+            exchange.to_string(),
+        ))
+    }
+}
+
+/// Look up an endpoint that is connected and not taken yet. The
+/// returned slot is always `Some`.
+fn endpoint<'a, T>(
+    processor: &Key,
+    endpoints: &'a mut IndexMap<Key, Option<T>>,
+    exchange: Key,
+) -> Result<&'a mut Option<T>, Error> {
+    let slot = endpoints
+        .get_mut(&exchange)
+        .ok_or_else(|| Error::connection_error(processor, &exchange))?;
+    if slot.is_none() {
+        return Err(Error::endpoint_taken(processor, &exchange));
+    }
+    Ok(slot)
+}
@@ -0,0 +1,304 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use derivative::Derivative;
+use easyflow_link::{Message, Receiver};
+use futures::{
+    future,
+    stream::{self, BoxStream, SelectAll, Stream, StreamExt as _},
+};
+use std::{collections::VecDeque, time::Duration};
+
+/// Get the timestamp of a message from the input at an index.
+type Extractor<T> = dyn Fn(usize, &T) -> Result<DateTime<Local>> + Send;
+
+/// The matching policy of a [Synchronizer].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// Match messages with identical timestamps.
+    Exact,
+    /// Match messages whose timestamps differ by at most `max_slop`.
+    Approximate { max_slop: Duration },
+}
+
+/// The options of a [Synchronizer].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncOptions {
+    pub policy: SyncPolicy,
+    /// The maximum number of pending messages per input. The oldest
+    /// message is dropped when the queue is full.
+    pub queue_size: usize,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            policy: SyncPolicy::Exact,
+            queue_size: 10,
+        }
+    }
+}
+
+/// The counts of emitted sets and dropped messages of a
+/// [Synchronizer].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SyncStats {
+    /// The number of emitted sets.
+    pub emitted: usize,
+    /// The number of dropped unmatched messages per input.
+    pub dropped: Vec<usize>,
+}
+
+/// Match messages from several inputs by timestamps, in the way of
+/// ROS `message_filters`.
+///
+/// Each emitted set contains exactly one message from every input,
+/// in the order of inputs. Timestamps are assumed to be
+/// non-decreasing on each input. Messages that cannot be matched any
+/// more are dropped and counted in [stats](Synchronizer::stats).
+///
+/// For the approximate policy, a set is emitted when it cannot be
+/// improved by messages to come. That is, every input whose message
+/// is not the latest one in the set has a later message queued, or
+/// is closed.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Synchronizer<T> {
+    #[derivative(Debug = "ignore")]
+    inputs: SelectAll<BoxStream<'static, (usize, Option<Result<T>>)>>,
+    #[derivative(Debug = "ignore")]
+    extractor: Box<Extractor<T>>,
+    #[derivative(Debug = "ignore")]
+    queues: Vec<VecDeque<(DateTime<Local>, T)>>,
+    closed: Vec<bool>,
+    options: SyncOptions,
+    stats: SyncStats,
+}
+
+impl Synchronizer<Message> {
+    /// Synchronize receivers by the timestamps of messages in the
+    /// envelope mode.
+    pub fn new(receivers: impl IntoIterator<Item = Receiver>, options: SyncOptions) -> Self {
+        Self::with_extractor(
+            receivers,
+            |index, message: &Message| {
+                let meta = message.meta.as_ref().ok_or_else(|| {
+                    anyhow!(
+                        "the message from input {index} has no timestamp. \
+                         enable the envelope mode on the exchange or use a timestamp extractor"
+                    )
+                })?;
+                Ok(meta.timestamp)
+            },
+            options,
+        )
+    }
+
+    /// Synchronize receivers by timestamps given by the `extractor`,
+    /// which is called with the input index and the message.
+    pub fn with_extractor<F>(
+        receivers: impl IntoIterator<Item = Receiver>,
+        extractor: F,
+        options: SyncOptions,
+    ) -> Self
+    where
+        F: Fn(usize, &Message) -> Result<DateTime<Local>> + Send + 'static,
+    {
+        let streams = receivers
+            .into_iter()
+            .map(|receiver| receiver.into_message_stream());
+        Self::from_streams(streams, extractor, options)
+    }
+}
+
+impl<T> Synchronizer<T>
+where
+    T: Send + 'static,
+{
+    /// Synchronize arbitrary streams by timestamps given by the
+    /// `extractor`.
+    pub fn from_streams<S, F>(
+        streams: impl IntoIterator<Item = S>,
+        extractor: F,
+        options: SyncOptions,
+    ) -> Self
+    where
+        S: Stream<Item = Result<T>> + Send + 'static,
+        F: Fn(usize, &T) -> Result<DateTime<Local>> + Send + 'static,
+    {
+        assert!(options.queue_size > 0, "queue_size must be positive");
+
+        let inputs: SelectAll<_> = streams
+            .into_iter()
+            .enumerate()
+            .map(|(index, stream)| {
+                // Mark the end of each input with `None`.
+                stream
+                    .map(Some)
+                    .chain(stream::once(future::ready(None)))
+                    .map(move |item| (index, item))
+                    .boxed()
+            })
+            .collect();
+        let len = inputs.len();
+
+        Self {
+            inputs,
+            extractor: Box::new(extractor),
+            queues: (0..len).map(|_| VecDeque::new()).collect(),
+            closed: vec![false; len],
+            options,
+            stats: SyncStats {
+                emitted: 0,
+                dropped: vec![0; len],
+            },
+        }
+    }
+
+    /// Receive the next matched set. It returns `None` when an input
+    /// is closed and no more sets can be matched.
+    pub async fn recv(&mut self) -> Result<Option<Vec<T>>> {
+        if self.queues.is_empty() {
+            return Ok(None);
+        }
+
+        loop {
+            match self.try_match() {
+                Match::Ready(set) => {
+                    self.stats.emitted += 1;
+                    return Ok(Some(set));
+                }
+                Match::Finished => {
+                    self.drop_all();
+                    return Ok(None);
+                }
+                Match::Pending => {}
+            }
+
+            let Some((index, item)) = self.inputs.next().await else {
+                // All inputs are closed.
+                self.closed.iter_mut().for_each(|closed| *closed = true);
+                continue;
+            };
+
+            match item {
+                Some(msg) => {
+                    let msg = msg?;
+                    let timestamp = (self.extractor)(index, &msg)?;
+                    self.push(index, timestamp, msg);
+                }
+                None => {
+                    self.closed[index] = true;
+                }
+            }
+        }
+    }
+
+    /// The counts of emitted sets and dropped messages so far.
+    pub fn stats(&self) -> &SyncStats {
+        &self.stats
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = Result<Vec<T>>> {
+        stream::try_unfold(self, |mut sync| async move {
+            let item = sync.recv().await?;
+            anyhow::Ok(item.map(|item| (item, sync)))
+        })
+    }
+
+    fn push(&mut self, index: usize, timestamp: DateTime<Local>, msg: T) {
+        let queue = &mut self.queues[index];
+        queue.push_back((timestamp, msg));
+
+        if queue.len() > self.options.queue_size {
+            queue.pop_front();
+            self.stats.dropped[index] += 1;
+        }
+    }
+
+    fn drop_front(&mut self, index: usize) {
+        self.queues[index].pop_front();
+        self.stats.dropped[index] += 1;
+    }
+
+    fn drop_all(&mut self) {
+        for (queue, dropped) in self.queues.iter_mut().zip(&mut self.stats.dropped) {
+            *dropped += queue.len();
+            queue.clear();
+        }
+    }
+
+    fn try_match(&mut self) -> Match<T> {
+        let max_slop = match self.options.policy {
+            SyncPolicy::Exact => chrono::Duration::zero(),
+            SyncPolicy::Approximate { max_slop } => {
+                chrono::Duration::from_std(max_slop).unwrap_or(chrono::Duration::MAX)
+            }
+        };
+
+        'retry: loop {
+            // Wait until every input has a message.
+            for (queue, &closed) in self.queues.iter().zip(&self.closed) {
+                if queue.is_empty() {
+                    return if closed {
+                        Match::Finished
+                    } else {
+                        Match::Pending
+                    };
+                }
+            }
+
+            let heads = || self.queues.iter().map(|queue| queue[0].0);
+            let (oldest, lower) = heads()
+                .enumerate()
+                .min_by_key(|&(_, timestamp)| timestamp)
+                .unwrap();
+            let upper = heads().max().unwrap();
+
+            // The oldest message is too far from the latest input.
+            if upper - lower > max_slop {
+                self.drop_front(oldest);
+                continue 'retry;
+            }
+
+            let mut pending = false;
+
+            for index in 0..self.queues.len() {
+                let queue = &self.queues[index];
+                if queue[0].0 == upper {
+                    continue;
+                }
+
+                match queue.get(1) {
+                    // A later message is closer to the latest input.
+                    Some(&(next, _)) if next <= upper => {
+                        self.drop_front(index);
+                        continue 'retry;
+                    }
+                    Some(_) => {}
+                    None => {
+                        if !self.closed[index] {
+                            pending = true;
+                        }
+                    }
+                }
+            }
+
+            if pending {
+                return Match::Pending;
+            }
+
+            let set = self
+                .queues
+                .iter_mut()
+                .map(|queue| queue.pop_front().unwrap().1)
+                .collect();
+            return Match::Ready(set);
+        }
+    }
+}
+
+enum Match<T> {
+    Ready(Vec<T>),
+    Pending,
+    Finished,
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use easyflow::Dataflow;
+use easyflow_config::Key;
+use serde_json::json;
+use std::path::Path;
+
+const CONFIG_DIR: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../easyflow-config/config-examples"
+);
+
+fn key(name: &str) -> Key {
+    name.parse().unwrap()
+}
+
+#[test]
+fn diff_test() -> Result<()> {
+    let config_dir = Path::new(CONFIG_DIR);
+    let old = Dataflow::open(config_dir.join("diff/old.json5"))?;
+    let new = Dataflow::open(config_dir.join("diff/new.json5"))?;
+
+    assert!(old.diff(&old).is_empty());
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.added_processors, [key("tracker")]);
+    assert_eq!(diff.removed_processors, [key("logger")]);
+    assert_eq!(diff.added_exchanges, [key("TRACK")]);
+    assert_eq!(diff.removed_exchanges, [key("LOG")]);
+
+    // only the changed field is reported
+    let changes = &diff.changed_exchanges[&key("VIDEO")];
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].field, "key");
+    assert_eq!(changes[0].old, Some(json!("camera/video")));
+    assert_eq!(changes[0].new, Some(json!("camera0/video")));
+    assert_eq!(diff.changed_exchanges.len(), 1);
+
+    let video = &diff.changed_connections[&key("VIDEO")];
+    assert_eq!(video.added_sources, [key("tracker")]);
+    assert!(video.removed_sources.is_empty());
+    assert!(video.added_sinks.is_empty());
+
+    let detection = &diff.changed_connections[&key("DETECTION")];
+    assert_eq!(detection.added_sources, [key("tracker")]);
+    assert_eq!(detection.removed_sources, [key("logger")]);
+
+    let log = &diff.changed_connections[&key("LOG")];
+    assert_eq!(log.removed_sources, [key("logger")]);
+    let track = &diff.changed_connections[&key("TRACK")];
+    assert_eq!(track.added_sinks, [key("tracker")]);
+
+    // modules are flattened before comparison
+    let template = Dataflow::open(config_dir.join("template.json5"))?;
+    let dir = tempfile::tempdir()?;
+    let flat_file = dir.path().join("template.json5");
+    template.save_flattened(&flat_file)?;
+    assert!(template.diff(&Dataflow::open(&flat_file)?).is_empty());
+
+    Ok(())
+}
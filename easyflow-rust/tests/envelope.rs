@@ -0,0 +1,49 @@
+use anyhow::Result;
+use easyflow::Dataflow;
+use std::{collections::BTreeMap, fs};
+
+const CONFIG: &str = r#"{
+    "version": "0.2.0",
+    "processors": ["camera", "merger"],
+    "exchanges": {
+        "VIDEO": { "type": "file", "dir": "video", "codec": "json", "envelope": true },
+    },
+    "connections": {
+        "VIDEO": { "<": ["camera"], ">": ["merger"] },
+    },
+}"#;
+
+#[tokio::test]
+async fn envelope_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("dataflow.json5");
+    fs::write(&file, CONFIG)?;
+    let dataflow = Dataflow::open(&file)?;
+
+    let mut sender = dataflow
+        .build_typed_sender_to::<String, _, _>("camera", "VIDEO")
+        .await?;
+    sender.send(&"first".to_string()).await?;
+    let headers: BTreeMap<_, _> = [("exposure".to_string(), "10ms".to_string())].into();
+    sender
+        .send_with_headers(&"second".to_string(), headers.clone())
+        .await?;
+
+    let mut io = dataflow.build_processor("merger").await?;
+    let mut receiver = io.take_typed_receiver::<String, _>("VIDEO")?;
+
+    let (value, meta) = receiver.recv_with_meta().await?.unwrap();
+    let meta = meta.unwrap();
+    assert_eq!(value, "first");
+    assert_eq!(meta.seq, 0);
+    assert_eq!(meta.producer.as_deref(), Some("camera"));
+    assert!(meta.headers.is_empty());
+
+    let (value, meta) = receiver.recv_with_meta().await?.unwrap();
+    let meta = meta.unwrap();
+    assert_eq!(value, "second");
+    assert_eq!(meta.seq, 1);
+    assert_eq!(meta.headers, headers);
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use easyflow::Dataflow;
+use easyflow_config::GraphConfig;
+use easyflow_link::Config as Exchange;
+use std::path::Path;
+
+const CONFIG_DIR: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../easyflow-config/config-examples"
+);
+
+#[test]
+fn save_flattened_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+
+    for name in [
+        "diamond.json5",
+        "template.json5",
+        "formats.toml",
+        "variables.json5",
+        "launch.json5",
+        "lint.json5",
+    ] {
+        let dataflow = Dataflow::open(Path::new(CONFIG_DIR).join(name))?;
+
+        let flat_file = dir.path().join(name);
+        dataflow.save_flattened(&flat_file)?;
+
+        // the flattened file has no modules and imports
+        let config = GraphConfig::open(&flat_file)?;
+        assert!(config.modules.is_none());
+        assert!(!config
+            .exchanges
+            .values()
+            .any(|exchange| matches!(exchange, Exchange::Import(_))));
+
+        let flat_dataflow = Dataflow::open(&flat_file)?;
+        assert_eq!(flat_dataflow, dataflow);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn save_flattened_package_test() -> Result<()> {
+    let config_dir = Path::new(CONFIG_DIR);
+    let dataflow = Dataflow::open_with_search_path(
+        config_dir.join("package/main.json5"),
+        [config_dir.join("package/lib")],
+    )?;
+
+    // the flattened file is opened without the search path
+    let dir = tempfile::tempdir()?;
+    let flat_file = dir.path().join("package.json5");
+    dataflow.save_flattened(&flat_file)?;
+    assert_eq!(Dataflow::open(&flat_file)?, dataflow);
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+use anyhow::Result;
+use easyflow::{AdjacencyEdge, Dataflow, DotOptions, EdgeDirection, NodeKind};
+use easyflow_config::{GraphConfig, Key};
+use std::{collections::HashSet, path::Path};
+
+const CONFIG_DIR: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../easyflow-config/config-examples"
+);
+
+#[test]
+fn adjacency_test() -> Result<()> {
+    let dataflow = Dataflow::open(Path::new(CONFIG_DIR).join("launch.json5"))?;
+    let adjacency = dataflow.adjacency();
+
+    let mut processors: Vec<_> = adjacency
+        .nodes
+        .iter()
+        .filter(|node| node.kind == NodeKind::Processor)
+        .map(|node| node.key.to_string())
+        .collect();
+    processors.sort();
+    assert_eq!(processors, ["camera", "merger", "recorder"]);
+
+    let video = adjacency
+        .nodes
+        .iter()
+        .find(|node| node.kind == NodeKind::Exchange && node.key.to_string() == "camera/VIDEO")
+        .unwrap();
+    assert_eq!(video.transport.as_deref(), Some("null"));
+
+    let edge = |processor: &str, exchange: &str, direction| AdjacencyEdge {
+        processor: processor.parse::<Key>().unwrap(),
+        exchange: exchange.parse::<Key>().unwrap(),
+        direction,
+    };
+    assert_eq!(adjacency.edges.len(), 4);
+    for expect in [
+        edge("camera", "camera/VIDEO", EdgeDirection::Output),
+        edge("merger", "camera/VIDEO", EdgeDirection::Input),
+        edge("merger", "OUTPUT", EdgeDirection::Output),
+        edge("recorder", "OUTPUT", EdgeDirection::Input),
+    ] {
+        assert!(adjacency.edges.contains(&expect), "missing {:?}", expect);
+    }
+
+    // the JSON file loads back to the same adjacency
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("graph.json");
+    dataflow.save_adjacency_file(&file)?;
+    let text = std::fs::read_to_string(&file)?;
+    assert_eq!(
+        serde_json::from_str::<easyflow::Adjacency>(&text)?,
+        adjacency
+    );
+
+    Ok(())
+}
+
+#[test]
+fn mermaid_test() -> Result<()> {
+    let dataflow = Dataflow::open(Path::new(CONFIG_DIR).join("launch.json5"))?;
+    let mermaid = dataflow.to_mermaid();
+
+    let mut lines = mermaid.lines();
+    assert_eq!(lines.next(), Some("flowchart LR"));
+    assert!(mermaid.contains(r#"["merger"]"#));
+    assert!(mermaid.contains(r#"{{"camera/VIDEO"}}"#));
+    assert_eq!(lines.filter(|line| line.contains(" --> ")).count(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn dot_clusters_test() -> Result<()> {
+    let dataflow = Dataflow::open(Path::new(CONFIG_DIR).join("recursive.json5"))?;
+    let dot = dataflow.to_dot();
+
+    // exchanges are grouped by modules and labelled by endpoints
+    assert!(dot.contains(r#"label="sub1";"#));
+    assert!(dot.contains(r#"label="sub2";"#));
+    assert!(dot.contains(r#"[label="sub1/amqp-first\namqp: FIRST at amqp://127.0.0.1:5672/%2f"][shape="hexagon"][color="darkorange"]"#));
+
+    Ok(())
+}
+
+#[test]
+fn dot_neighborhood_test() -> Result<()> {
+    let dataflow = Dataflow::open(Path::new(CONFIG_DIR).join("recursive.json5"))?;
+
+    let options = DotOptions {
+        neighborhood: Some("second".parse().unwrap()),
+        ..Default::default()
+    };
+    let dot = dataflow.to_dot_with(&options)?;
+    for label in ["first", "second", "third", "amqp-second", "sub1/amqp-first"] {
+        assert!(
+            dot.contains(&format!("[label=\"{}", label)),
+            "missing {}",
+            label
+        );
+    }
+    assert!(!dot.contains("fourth"));
+    assert!(!dot.contains(r#"label="sub2";"#));
+
+    let options = DotOptions {
+        neighborhood: Some("nobody".parse().unwrap()),
+        ..Default::default()
+    };
+    assert!(dataflow.to_dot_with(&options).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn dot_node_id_test() -> Result<()> {
+    // keys that look alike once escaped, or start with a digit
+    let config: GraphConfig = json5::from_str(
+        r#"{
+            "version": "0.2.0",
+            "processors": ["cam-x", "cam_x", "0cam"],
+            "exchanges": {
+                "cam/x": { "type": "null" },
+                "cam-x": { "type": "null" },
+            },
+            "connections": {
+                "cam/x": { "<": ["cam-x"], ">": ["cam_x"] },
+                "cam-x": { "<": ["cam_x"], ">": ["0cam"] },
+            },
+        }"#,
+    )?;
+    let dataflow = Dataflow::from_config(config)?;
+    let dot = dataflow.to_dot();
+
+    let ids: HashSet<_> = dot
+        .lines()
+        .filter_map(|line| line.trim().split_once("[label=").map(|(id, _)| id))
+        .collect();
+    assert_eq!(ids.len(), 5, "{}", dot);
+    assert!(dot.contains(r#"[label="cam/x\nnull"]"#));
+    assert!(dot.contains(r#"[label="0cam"]"#));
+    assert_eq!(dot.lines().filter(|line| line.contains(" -> ")).count(), 4);
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use easyflow::Dataflow;
+use easyflow_config::{Key, RestartPolicy};
+use std::path::Path;
+
+const CONFIG_DIR: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../easyflow-config/config-examples"
+);
+
+#[test]
+fn launch_test() -> Result<()> {
+    let config_dir = Path::new(CONFIG_DIR).canonicalize()?;
+    let dataflow = Dataflow::open(config_dir.join("launch.json5"))?;
+    let launches = dataflow.launches();
+
+    // launch specs in modules are merged
+    let camera: Key = "camera".parse().unwrap();
+    let merger: Key = "merger".parse().unwrap();
+    let recorder: Key = "recorder".parse().unwrap();
+    assert_eq!(launches.len(), 3);
+
+    // working directories default to the directory of the declaring file
+    assert_eq!(
+        launches[&camera].working_dir.as_deref(),
+        Some(config_dir.join("launch").as_path())
+    );
+    assert_eq!(
+        launches[&merger].working_dir.as_deref(),
+        Some(config_dir.as_path())
+    );
+    assert_eq!(
+        launches[&recorder].working_dir.as_deref(),
+        Some(config_dir.join("launch").as_path())
+    );
+
+    assert_eq!(launches[&merger].env["RUST_LOG"], "info");
+    assert_eq!(launches[&merger].restart, RestartPolicy::Never);
+    assert_eq!(launches[&recorder].restart, RestartPolicy::OnFailure);
+
+    // consumers are started before producers
+    assert_eq!(dataflow.launch_order(), [&recorder, &merger, &camera]);
+
+    Ok(())
+}
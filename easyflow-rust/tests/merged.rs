@@ -0,0 +1,111 @@
+use anyhow::Result;
+use easyflow::{ClosePolicy, Dataflow, Error};
+use easyflow_config::Key;
+use std::fs;
+
+const CONFIG: &str = r#"{
+    "version": "0.2.0",
+    "processors": ["camera", "lidar", "merger"],
+    "exchanges": {
+        "VIDEO": { "type": "file", "dir": "video" },
+        "LIDAR": { "type": "file", "dir": "lidar" },
+        "EMPTY": { "type": "null" },
+    },
+    "connections": {
+        "VIDEO": { "<": ["camera"], ">": ["merger"] },
+        "LIDAR": { "<": ["lidar"], ">": ["merger"] },
+        "EMPTY": { ">": ["merger"] },
+    },
+}"#;
+
+async fn open(dir: &tempfile::TempDir) -> Result<Dataflow> {
+    let file = dir.path().join("dataflow.json5");
+    fs::write(&file, CONFIG)?;
+    let dataflow = Dataflow::open(&file)?;
+
+    let mut video = dataflow.build_sender_to("camera", "VIDEO").await?;
+    let mut lidar = dataflow.build_sender_to("lidar", "LIDAR").await?;
+    for index in 0..3 {
+        video.send(format!("frame{index}").into_bytes()).await?;
+    }
+    for index in 0..2 {
+        lidar.send(format!("points{index}").into_bytes()).await?;
+    }
+
+    Ok(dataflow)
+}
+
+#[tokio::test]
+async fn merged_continue_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dataflow = open(&dir).await?;
+
+    let mut receiver = dataflow
+        .build_merged_receiver_all("merger", ClosePolicy::Continue)
+        .await?;
+    assert_eq!(receiver.exchanges().len(), 3);
+
+    let mut video = vec![];
+    let mut lidar = vec![];
+    while let Some((key, payload)) = receiver.recv().await? {
+        let payload = String::from_utf8(payload)?;
+        match key.to_string().as_str() {
+            "VIDEO" => video.push(payload),
+            "LIDAR" => lidar.push(payload),
+            key => panic!("unexpected exchange '{key}'"),
+        }
+    }
+
+    assert_eq!(video, ["frame0", "frame1", "frame2"]);
+    assert_eq!(lidar, ["points0", "points1"]);
+    for key in ["VIDEO", "LIDAR", "EMPTY"] {
+        let key: Key = key.parse().unwrap();
+        assert!(receiver.is_closed(&key));
+    }
+    assert!(receiver.recv().await?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn merged_stop_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dataflow = open(&dir).await?;
+
+    let mut receiver = dataflow
+        .build_merged_receiver("merger", ["VIDEO", "EMPTY"], ClosePolicy::Stop)
+        .await?;
+
+    let mut count = 0;
+    while receiver.recv().await?.is_some() {
+        count += 1;
+    }
+
+    // It stops once the null exchange is closed.
+    let empty: Key = "EMPTY".parse().unwrap();
+    assert!(count <= 3);
+    assert!(receiver.is_closed(&empty));
+    assert_eq!(receiver.closed().count(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn merged_error_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dataflow = open(&dir).await?;
+
+    let err = dataflow
+        .build_merged_receiver("merger", ["VIDEO", "MISSING"], ClosePolicy::Stop)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::ConnectionError { .. }));
+
+    let err = dataflow
+        .build_merged_receiver_all("camera", ClosePolicy::Stop)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::NoInputAvailable { .. }));
+
+    Ok(())
+}
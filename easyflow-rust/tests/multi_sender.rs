@@ -0,0 +1,128 @@
+use anyhow::Result;
+use easyflow::{Dataflow, Error, FailurePolicy};
+use std::{collections::BTreeMap, fs};
+
+const CONFIG: &str = r#"{
+    "version": "0.2.0",
+    "processors": ["merger", "viewer", "logger"],
+    "exchanges": {
+        "DISPLAY": { "type": "file", "dir": "display", "envelope": true },
+        "LOG": { "type": "file", "dir": "log" },
+        "DISCARD": { "type": "null", "envelope": true },
+    },
+    "connections": {
+        "DISPLAY": { "<": ["merger"], ">": ["viewer"] },
+        "LOG": { "<": ["merger"], ">": ["logger"] },
+        "DISCARD": { "<": ["merger"] },
+    },
+}"#;
+
+fn open(dir: &tempfile::TempDir) -> Result<Dataflow> {
+    let file = dir.path().join("dataflow.json5");
+    fs::write(&file, CONFIG)?;
+    Dataflow::open(&file)
+}
+
+fn headers() -> BTreeMap<String, String> {
+    [("frame".to_string(), "0".to_string())].into()
+}
+
+#[tokio::test]
+async fn multi_sender_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dataflow = open(&dir)?;
+
+    let mut sender = dataflow
+        .build_multi_sender_all("merger", FailurePolicy::AllOrError)
+        .await?;
+    assert_eq!(sender.exchanges().count(), 3);
+
+    let report = sender.send(&b"merged"[..]).await?;
+    assert!(report.is_ok());
+    assert_eq!(report.succeeded().count(), 3);
+
+    let mut display = dataflow.build_receiver_from("viewer", "DISPLAY").await?;
+    let message = display.recv_message().await?.unwrap();
+    assert_eq!(message.payload, b"merged");
+    assert_eq!(message.meta.unwrap().producer.as_deref(), Some("merger"));
+
+    let mut log = dataflow.build_receiver_from("logger", "LOG").await?;
+    assert_eq!(log.recv().await?.as_deref(), Some(&b"merged"[..]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn multi_sender_policy_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dataflow = open(&dir)?;
+
+    // The LOG exchange is not in the envelope mode and rejects headers.
+    let mut sender = dataflow
+        .build_multi_sender_all("merger", FailurePolicy::AllOrError)
+        .await?;
+    let err = sender
+        .send_with_headers(&b"merged"[..], headers())
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "unable to send to exchange 'LOG'");
+
+    // Other sends are completed rather than cancelled.
+    let mut display = dataflow.build_receiver_from("viewer", "DISPLAY").await?;
+    let message = display.recv_message().await?.unwrap();
+    assert_eq!(message.payload, b"merged");
+    assert_eq!(message.meta.unwrap().headers, headers());
+
+    for policy in [FailurePolicy::BestEffort, FailurePolicy::Report] {
+        let mut sender = dataflow.build_multi_sender_all("merger", policy).await?;
+        let report = sender.send_with_headers(&b"merged"[..], headers()).await?;
+        assert!(!report.is_ok());
+
+        let succeeded: Vec<_> = report.succeeded().map(|key| key.to_string()).collect();
+        assert_eq!(succeeded, ["DISPLAY", "DISCARD"]);
+        let failed: Vec<_> = report.failed().map(|(key, _)| key.to_string()).collect();
+        assert_eq!(failed, ["LOG"]);
+    }
+
+    // Best effort fails only if all exchanges fail.
+    let mut sender = dataflow
+        .build_multi_sender("merger", ["LOG"], FailurePolicy::BestEffort)
+        .await?;
+    let err = sender
+        .send_with_headers(&b"merged"[..], headers())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unable to send to any exchange: unable to send to exchange 'LOG': \
+         headers are only supported by exchanges in the envelope mode"
+    );
+
+    let mut sender = dataflow
+        .build_multi_sender("merger", ["LOG"], FailurePolicy::Report)
+        .await?;
+    let report = sender.send_with_headers(&b"merged"[..], headers()).await?;
+    assert_eq!(report.failed().count(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn multi_sender_error_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dataflow = open(&dir)?;
+
+    let err = dataflow
+        .build_multi_sender("merger", ["LOG", "MISSING"], FailurePolicy::AllOrError)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::ConnectionError { .. }));
+
+    let err = dataflow
+        .build_multi_sender_all("viewer", FailurePolicy::AllOrError)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::NoOutputAvailable { .. }));
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use easyflow::Dataflow;
+use easyflow_config::Key;
+use easyflow_link::Config as Exchange;
+use std::{fs, path::Path};
+
+const CONFIG_DIR: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../easyflow-config/config-examples"
+);
+
+fn key(name: &str) -> Key {
+    name.parse().unwrap()
+}
+
+#[test]
+fn overlay_test() -> Result<()> {
+    let config_dir = Path::new(CONFIG_DIR).canonicalize()?;
+    let base_file = config_dir.join("template.json5");
+    let overlay_file = config_dir.join("overlay/ci.json5");
+
+    let base = Dataflow::open(&base_file)?;
+    let dataflow = Dataflow::open_with_overlays(&base_file, [&overlay_file])?;
+
+    // the exchange is replaced
+    assert!(matches!(
+        dataflow.exchanges()[&key("cam0/VIDEO")],
+        Exchange::Null(_)
+    ));
+
+    // the exchange is patched and paths are relative to the overlay
+    let Exchange::File(file) = &dataflow.exchanges()[&key("cam1/VIDEO")] else {
+        panic!("expect a file exchange");
+    };
+    assert_eq!(*file.dir, config_dir.join("overlay/recordings/cam1"));
+    assert!(file.auto_clean);
+
+    // the processor and connection are added
+    assert!(dataflow.processors().contains(&key("recorder")));
+    let conn = &dataflow.connections()[&key("cam1/VIDEO")];
+    assert!(conn.source_iter().eq([&key("recorder")]));
+
+    // the connection is disabled
+    let conn = &dataflow.connections()[&key("cam0/VIDEO")];
+    assert_eq!(conn.sink_iter().count(), 0);
+
+    let diff = base.diff(&dataflow);
+    assert_eq!(diff.added_processors, [key("recorder")]);
+    assert_eq!(diff.changed_exchanges.len(), 2);
+
+    // the dataflow is serialized with overlays applied
+    let dir = tempfile::tempdir()?;
+    let flat_file = dir.path().join("flat.json5");
+    dataflow.save_flattened(&flat_file)?;
+    assert_eq!(Dataflow::open(&flat_file)?, dataflow);
+
+    Ok(())
+}
+
+#[test]
+fn overlay_invalid_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let base_file = Path::new(CONFIG_DIR).join("template.json5");
+    let overlay_file = dir.path().join("overlay.json5");
+
+    for (overlay, message) in [
+        (
+            r#"{"version": "0.2.0", "patches": {"cam2/VIDEO": {"auto_clean": true}}}"#,
+            "unable to patch exchange 'cam2/VIDEO'",
+        ),
+        (
+            r#"{"version": "0.2.0", "connections": {"cam0/VIDEO": {">": ["recorder"]}}}"#,
+            "'recorder' is not a declared processor",
+        ),
+        (
+            r#"{"version": "0.2.0", "disabled_connections": {"cam0/VIDEO": {">": ["cam0/camera"]}}}"#,
+            "does not have 'cam0/camera' in '>'",
+        ),
+    ] {
+        fs::write(&overlay_file, overlay)?;
+        let err = Dataflow::open_with_overlays(&base_file, [&overlay_file]).unwrap_err();
+        let err = format!("{:#}", err);
+        assert!(err.contains(message), "unexpected error: {}", err);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use easyflow::{Dataflow, Error};
+use std::path::Path;
+
+const CONFIG_FILE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../easyflow-config/config-examples/launch.json5"
+);
+
+#[tokio::test]
+async fn build_processor_test() -> Result<()> {
+    let dataflow = Dataflow::open(Path::new(CONFIG_FILE))?;
+    let mut io = dataflow.build_processor("merger").await?;
+
+    assert_eq!(io.processor().to_string(), "merger");
+    let inputs: Vec<_> = io.inputs().map(|key| key.to_string()).collect();
+    let outputs: Vec<_> = io.outputs().map(|key| key.to_string()).collect();
+    assert_eq!(inputs, ["camera/VIDEO"]);
+    assert_eq!(outputs, ["OUTPUT"]);
+
+    io.sender("OUTPUT")?.send(&[1, 2, 3][..]).await?;
+    assert!(matches!(
+        io.receiver("OUTPUT"),
+        Err(Error::ConnectionError { .. })
+    ));
+
+    let _receiver = io.take_receiver("camera/VIDEO")?;
+    assert!(matches!(
+        io.take_receiver("camera/VIDEO"),
+        Err(Error::EndpointTaken { .. })
+    ));
+
+    assert!(matches!(
+        dataflow.build_processor("nobody").await,
+        Err(Error::ProcessorNotFound { .. })
+    ));
+
+    Ok(())
+}
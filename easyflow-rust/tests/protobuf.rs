@@ -0,0 +1,84 @@
+#![cfg(feature = "protobuf")]
+
+use anyhow::Result;
+use easyflow::Dataflow;
+use prost::{Message, Name};
+use std::fs;
+
+#[derive(Clone, PartialEq, Message)]
+struct Frame {
+    #[prost(uint64, tag = "1")]
+    seq: u64,
+    #[prost(bytes = "vec", tag = "2")]
+    data: Vec<u8>,
+}
+
+impl Name for Frame {
+    const NAME: &'static str = "Frame";
+    const PACKAGE: &'static str = "sensors";
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Other {
+    #[prost(string, tag = "1")]
+    name: String,
+}
+
+impl Name for Other {
+    const NAME: &'static str = "Other";
+    const PACKAGE: &'static str = "sensors";
+}
+
+const CONFIG: &str = r#"{
+    "version": "0.2.0",
+    "processors": ["producer", "consumer"],
+    "exchanges": {
+        "FRAME": { "type": "file", "dir": "frame", "message_type": "sensors.Frame" },
+    },
+    "connections": {
+        "FRAME": { "<": ["producer"], ">": ["consumer"] },
+    },
+}"#;
+
+#[tokio::test]
+async fn protobuf_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("dataflow.json5");
+    fs::write(&file, CONFIG)?;
+    let dataflow = Dataflow::open(&file)?;
+
+    let frame = Frame {
+        seq: 3,
+        data: vec![1, 4, 1, 5],
+    };
+
+    let mut sender = dataflow
+        .build_protobuf_sender_to::<Frame, _, _>("producer", "FRAME")
+        .await?;
+    sender.send(&frame).await?;
+
+    let mut receiver = dataflow
+        .build_protobuf_receiver_from::<Frame, _, _>("consumer", "FRAME")
+        .await?;
+    assert_eq!(receiver.recv().await?, Some(frame));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn protobuf_type_mismatch_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("dataflow.json5");
+    fs::write(&file, CONFIG)?;
+    let dataflow = Dataflow::open(&file)?;
+
+    let err = dataflow
+        .build_protobuf_sender_to::<Other, _, _>("producer", "FRAME")
+        .await
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("message type mismatch on exchange 'FRAME'"));
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use anyhow::Result;
+use easyflow::{Dataflow, Error};
+use easyflow_config::{GraphConfig, Key};
+use std::path::Path;
+
+const CONFIG_DIR: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../easyflow-config/config-examples"
+);
+
+fn keys(keys: Vec<&Key>) -> Vec<String> {
+    keys.into_iter().map(|key| key.to_string()).collect()
+}
+
+#[test]
+fn query_test() -> Result<()> {
+    let dataflow = Dataflow::open(Path::new(CONFIG_DIR).join("launch.json5"))?;
+
+    assert_eq!(keys(dataflow.inputs_of("merger")?), ["camera/VIDEO"]);
+    assert_eq!(keys(dataflow.outputs_of("merger")?), ["OUTPUT"]);
+    assert!(dataflow.outputs_of("recorder")?.is_empty());
+    assert!(matches!(
+        dataflow.inputs_of("nobody"),
+        Err(Error::ProcessorNotFound { .. })
+    ));
+
+    assert_eq!(keys(dataflow.producers_of("camera/VIDEO")?), ["camera"]);
+    assert_eq!(keys(dataflow.consumers_of("OUTPUT")?), ["recorder"]);
+    assert!(matches!(
+        dataflow.producers_of("NOTHING"),
+        Err(Error::ExchangeNotFound { .. })
+    ));
+
+    assert_eq!(
+        dataflow.module_of_exchange("camera/VIDEO")?.to_string(),
+        "camera"
+    );
+    assert_eq!(dataflow.module_of_exchange("OUTPUT")?.to_string(), "");
+    assert_eq!(
+        dataflow.module_of_processor("camera")?.to_string(),
+        "camera"
+    );
+    assert_eq!(dataflow.module_of_processor("merger")?.to_string(), "");
+
+    assert_eq!(
+        keys(dataflow.topological_order()?),
+        ["camera", "merger", "recorder"]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn topological_order_cycle_test() -> Result<()> {
+    let config: GraphConfig = json5::from_str(
+        r#"{
+            "version": "0.2.0",
+            "processors": ["first", "second"],
+            "exchanges": {
+                "A": { "type": "null" },
+                "B": { "type": "null" },
+            },
+            "connections": {
+                "A": { "<": ["first"], ">": ["second"] },
+                "B": { "<": ["second"], ">": ["first"] },
+            },
+        }"#,
+    )?;
+    let dataflow = Dataflow::from_config(config)?;
+
+    let Err(Error::CyclicDataflow { processors }) = dataflow.topological_order() else {
+        panic!("the cycle is not detected");
+    };
+    assert_eq!(processors.len(), 2);
+
+    Ok(())
+}
+
+#[cfg(feature = "petgraph")]
+#[test]
+fn petgraph_test() -> Result<()> {
+    use easyflow::NodeKind;
+    use petgraph::algo::toposort;
+
+    let dataflow = Dataflow::open(Path::new(CONFIG_DIR).join("launch.json5"))?;
+    let graph = dataflow.to_petgraph();
+    assert_eq!(graph.node_count(), 5);
+    assert_eq!(graph.edge_count(), 4);
+
+    let order: Vec<_> = toposort(&graph, None)
+        .unwrap()
+        .into_iter()
+        .map(|index| &graph[index])
+        .filter(|node| node.kind == NodeKind::Processor)
+        .map(|node| node.key.to_string())
+        .collect();
+    assert_eq!(order, ["camera", "merger", "recorder"]);
+
+    Ok(())
+}
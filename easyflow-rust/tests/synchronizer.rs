@@ -0,0 +1,172 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, TimeZone as _};
+use easyflow::{Dataflow, SyncOptions, SyncPolicy, SyncStats, Synchronizer};
+use futures::stream::{self, TryStreamExt as _};
+use std::{fs, time::Duration};
+
+/// A message stamped at `millis` milliseconds.
+type Stamped = (i64, &'static str);
+
+fn time(millis: i64) -> DateTime<Local> {
+    Local.timestamp_millis_opt(millis).unwrap()
+}
+
+fn synchronizer(inputs: Vec<Vec<Stamped>>, options: SyncOptions) -> Synchronizer<Stamped> {
+    let streams = inputs
+        .into_iter()
+        .map(|input| stream::iter(input.into_iter().map(anyhow::Ok)));
+    Synchronizer::from_streams(streams, |_, &(millis, _)| Ok(time(millis)), options)
+}
+
+async fn collect(sync: Synchronizer<Stamped>) -> Result<Vec<Vec<&'static str>>> {
+    sync.into_stream()
+        .map_ok(|set| set.into_iter().map(|(_, name)| name).collect())
+        .try_collect()
+        .await
+}
+
+#[tokio::test]
+async fn exact_test() -> Result<()> {
+    let mut sync = synchronizer(
+        vec![
+            vec![(0, "a0"), (10, "a1"), (20, "a2"), (30, "a3")],
+            vec![(0, "b0"), (20, "b2"), (30, "b3")],
+        ],
+        SyncOptions::default(),
+    );
+
+    let mut sets = vec![];
+    while let Some(set) = sync.recv().await? {
+        sets.push(set.into_iter().map(|(_, name)| name).collect::<Vec<_>>());
+    }
+
+    assert_eq!(sets, [["a0", "b0"], ["a2", "b2"], ["a3", "b3"]]);
+    assert_eq!(
+        sync.stats(),
+        &SyncStats {
+            emitted: 3,
+            dropped: vec![1, 0],
+        }
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn approximate_test() -> Result<()> {
+    let options = SyncOptions {
+        policy: SyncPolicy::Approximate {
+            max_slop: Duration::from_millis(5),
+        },
+        ..Default::default()
+    };
+
+    // "a1" is closer to "b1" than "a0", and "c3" has no match.
+    let sync = synchronizer(
+        vec![
+            vec![(0, "a0"), (9, "a1"), (20, "a2"), (41, "a4")],
+            vec![(10, "b1"), (22, "b2"), (40, "b4")],
+            vec![(11, "c1"), (19, "c2"), (30, "c3"), (43, "c4")],
+        ],
+        options,
+    );
+
+    assert_eq!(
+        collect(sync).await?,
+        [["a1", "b1", "c1"], ["a2", "b2", "c2"], ["a4", "b4", "c4"]]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn queue_size_test() -> Result<()> {
+    let options = SyncOptions {
+        queue_size: 2,
+        ..Default::default()
+    };
+    let mut sync = synchronizer(
+        vec![
+            vec![(0, "a0"), (10, "a1"), (20, "a2"), (30, "a3")],
+            vec![(40, "b4")],
+        ],
+        options,
+    );
+
+    assert!(sync.recv().await?.is_none());
+    assert_eq!(
+        sync.stats(),
+        &SyncStats {
+            emitted: 0,
+            dropped: vec![4, 1],
+        }
+    );
+
+    Ok(())
+}
+
+const CONFIG: &str = r#"{
+    "version": "0.2.0",
+    "processors": ["camera", "lidar", "merger"],
+    "exchanges": {
+        "VIDEO": { "type": "file", "dir": "video", "envelope": true },
+        "LIDAR": { "type": "file", "dir": "lidar", "envelope": true },
+        "PLAIN": { "type": "file", "dir": "plain" },
+    },
+    "connections": {
+        "VIDEO": { "<": ["camera"], ">": ["merger"] },
+        "LIDAR": { "<": ["lidar"], ">": ["merger"] },
+        "PLAIN": { "<": ["lidar"], ">": ["merger"] },
+    },
+}"#;
+
+#[tokio::test]
+async fn envelope_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("dataflow.json5");
+    fs::write(&file, CONFIG)?;
+    let dataflow = Dataflow::open(&file)?;
+
+    let mut video = dataflow.build_sender_to("camera", "VIDEO").await?;
+    let mut lidar = dataflow.build_sender_to("lidar", "LIDAR").await?;
+    let mut plain = dataflow.build_sender_to("lidar", "PLAIN").await?;
+    for _ in 0..3 {
+        video.send(&b"frame"[..]).await?;
+        lidar.send(&b"points"[..]).await?;
+    }
+    plain.send(&b"points"[..]).await?;
+
+    let mut io = dataflow.build_processor("merger").await?;
+    let receivers = [io.take_receiver("VIDEO")?, io.take_receiver("LIDAR")?];
+    let options = SyncOptions {
+        policy: SyncPolicy::Approximate {
+            max_slop: Duration::from_secs(1),
+        },
+        ..Default::default()
+    };
+    let sets: Vec<_> = Synchronizer::new(receivers, options)
+        .into_stream()
+        .try_collect()
+        .await?;
+
+    assert_eq!(sets.len(), 3);
+    for (seq, set) in sets.iter().enumerate() {
+        assert_eq!(set[0].payload, b"frame");
+        assert_eq!(set[1].payload, b"points");
+        assert_eq!(set[0].meta.as_ref().unwrap().seq, seq as u64);
+        assert_eq!(
+            set[1].meta.as_ref().unwrap().producer.as_deref(),
+            Some("lidar")
+        );
+    }
+
+    // messages without envelopes have no timestamps
+    let receivers = [io.take_receiver("PLAIN")?];
+    let err = Synchronizer::new(receivers, SyncOptions::default())
+        .recv()
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("has no timestamp"));
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use anyhow::Result;
+use easyflow::{Codec, Dataflow};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Packet {
+    seq: u64,
+    data: Vec<u8>,
+}
+
+const CONFIG: &str = r#"{
+    "version": "0.2.0",
+    "processors": ["producer", "consumer"],
+    "exchanges": {
+        "BINCODE": { "type": "file", "dir": "bincode" },
+        "JSON": { "type": "file", "dir": "json", "codec": "json" },
+        "MSGPACK": { "type": "file", "dir": "msgpack", "codec": "msgpack" },
+        "CBOR": { "type": "file", "dir": "cbor", "codec": "cbor" },
+    },
+    "connections": {
+        "BINCODE": { "<": ["producer"], ">": ["consumer"] },
+        "JSON": { "<": ["producer"], ">": ["consumer"] },
+        "MSGPACK": { "<": ["producer"], ">": ["consumer"] },
+        "CBOR": { "<": ["producer"], ">": ["consumer"] },
+    },
+}"#;
+
+#[tokio::test]
+async fn typed_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("dataflow.json5");
+    fs::write(&file, CONFIG)?;
+    let dataflow = Dataflow::open(&file)?;
+
+    let packet = Packet {
+        seq: 7,
+        data: vec![3, 1, 4, 1, 5],
+    };
+
+    let exchanges = [
+        ("BINCODE", Codec::Bincode),
+        ("JSON", Codec::Json),
+        ("MSGPACK", Codec::MessagePack),
+        ("CBOR", Codec::Cbor),
+    ];
+
+    let mut producer = dataflow.build_processor("producer").await?;
+    for (exchange, codec) in exchanges {
+        let mut sender = producer.take_typed_sender(exchange)?;
+        assert_eq!(sender.codec(), codec);
+        sender.send(&packet).await?;
+    }
+
+    // the file receiver lists the messages written so far
+    let mut consumer = dataflow.build_processor("consumer").await?;
+    for (exchange, codec) in exchanges {
+        let mut receiver = consumer.take_typed_receiver::<Packet, _>(exchange)?;
+        assert_eq!(receiver.codec(), codec);
+        assert_eq!(receiver.recv().await?, Some(packet.clone()));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn typed_decode_error_test() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("dataflow.json5");
+    fs::write(&file, CONFIG)?;
+    let dataflow = Dataflow::open(&file)?;
+
+    let mut sender = dataflow.build_sender_to("producer", "JSON").await?;
+    sender.send(&b"not json"[..]).await?;
+
+    let mut receiver = dataflow
+        .build_typed_receiver_from::<Packet, _, _>("consumer", "JSON")
+        .await?;
+    let err = receiver.recv().await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unable to decode json message from exchange 'JSON'"
+    );
+
+    Ok(())
+}
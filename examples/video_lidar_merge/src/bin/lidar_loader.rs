@@ -13,14 +13,15 @@ async fn main() -> Result<()> {
 
     let config_file = concat!(env!("CARGO_MANIFEST_DIR"), "/dataflow.json5");
     let dataflow = Dataflow::open(config_file)?;
-    let mut sender = dataflow.build_sender("lidar_loader").await?;
+    let mut sender = dataflow
+        .build_typed_sender_to("lidar_loader", "LIDAR")
+        .await?;
 
     loop {
         let points: Vec<[f32; 3]> = (0..1000).map(|_| rng.gen()).collect();
         let packet = LidarPacket { points };
 
-        let payload = bincode::serialize(&packet)?;
-        sender.send(payload).await?;
+        sender.send(&packet).await?;
 
         eprintln!("sent one lidar packet");
 
@@ -1,8 +1,8 @@
 //! A fake video data loader.
 
 use anyhow::Result;
-use easyflow::Dataflow;
-use futures::try_join;
+use easyflow::{Dataflow, SyncOptions, SyncPolicy, Synchronizer};
+use std::time::Duration;
 use video_lidar_merge::{LidarPacket, MergePacket, VideoPacket};
 
 #[tokio::main]
@@ -10,21 +10,26 @@ async fn main() -> Result<()> {
     let config_file = concat!(env!("CARGO_MANIFEST_DIR"), "/dataflow.json5");
     let dataflow = Dataflow::open(config_file)?;
 
-    let mut video_receiver = dataflow.build_receiver_from("merger", "VIDEO").await?;
-    let mut lidar_receiver = dataflow.build_receiver_from("merger", "LIDAR").await?;
-    let mut sender = dataflow.build_sender_to("merger", "OUTPUT").await?;
-
-    loop {
-        // Collect input messages
-        let packets = try_join!(video_receiver.recv(), lidar_receiver.recv())?;
-        let (Some(video_payload), Some(lidar_payload)) = packets else {
-            eprintln!("receiver closed");
-            break;
-        };
-
-        // Decode packets
-        let video_packet: VideoPacket = bincode::deserialize(&video_payload)?;
-        let lidar_packet: LidarPacket = bincode::deserialize(&lidar_payload)?;
+    let mut io = dataflow.build_processor("merger").await?;
+    let video_codec = io.codec("VIDEO")?;
+    let lidar_codec = io.codec("LIDAR")?;
+    let mut sender = io.take_typed_sender("OUTPUT")?;
+
+    // Pair video and lidar messages sent at most 0.5 seconds apart
+    let receivers = [io.take_receiver("VIDEO")?, io.take_receiver("LIDAR")?];
+    let options = SyncOptions {
+        policy: SyncPolicy::Approximate {
+            max_slop: Duration::from_millis(500),
+        },
+        queue_size: 10,
+    };
+    let mut synchronizer = Synchronizer::new(receivers, options);
+
+    // Collect input messages
+    while let Some(messages) = synchronizer.recv().await? {
+        let [video_message, lidar_message] = <[_; 2]>::try_from(messages).unwrap();
+        let video_packet: VideoPacket = video_codec.decode(&video_message.payload)?;
+        let lidar_packet: LidarPacket = lidar_codec.decode(&lidar_message.payload)?;
 
         // Merge input packets into one
         let output_packet = MergePacket {
@@ -34,12 +39,15 @@ async fn main() -> Result<()> {
         };
 
         // Publish the output package
-        let output_text = serde_json::to_string_pretty(&output_packet)?;
-        let output_payload = output_text.as_bytes();
-        sender.send(output_payload).await?;
+        sender.send(&output_packet).await?;
 
         eprintln!("sent one merged packet");
     }
 
+    eprintln!(
+        "receiver closed, dropped {:?} unmatched messages",
+        synchronizer.stats().dropped
+    );
+
     Ok(())
 }
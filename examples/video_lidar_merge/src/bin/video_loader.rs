@@ -13,7 +13,9 @@ async fn main() -> Result<()> {
 
     let config_file = concat!(env!("CARGO_MANIFEST_DIR"), "/dataflow.json5");
     let dataflow = Dataflow::open(config_file)?;
-    let mut sender = dataflow.build_sender("video_loader").await?;
+    let mut sender = dataflow
+        .build_typed_sender_to("video_loader", "VIDEO")
+        .await?;
 
     loop {
         let h = 640;
@@ -23,8 +25,7 @@ async fn main() -> Result<()> {
         let image: Vec<u8> = (0..(h * w)).map(|_| rng.gen()).collect();
         let packet = VideoPacket { dimension, image };
 
-        let payload = bincode::serialize(&packet)?;
-        sender.send(payload).await?;
+        sender.send(&packet).await?;
 
         eprintln!("sent one video packet");
 